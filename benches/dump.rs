@@ -0,0 +1,41 @@
+//! Times the three builders against an extracted ROM, given with `USUM_ROM_PATH`. Without
+//! one nothing is measured.
+
+use std::{env, path::PathBuf};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use usum_extractor::{
+    garc, garc_files, learnset, moves, options::Options, pokemon, text::TextFile, RomData,
+};
+
+fn dump(c: &mut Criterion) {
+    let Some(rom_path) = env::var_os("USUM_ROM_PATH").map(PathBuf::from) else {
+        eprintln!("USUM_ROM_PATH isn't set, skipping the dump benchmarks");
+        return;
+    };
+    let options = Options {
+        rom_path: rom_path.clone(),
+        ..Default::default()
+    };
+    let text_path = rom_path.join(garc_files::BASE_PATH).join(garc_files::TEXT);
+    let text_files = garc::read_files::<TextFile>(&garc::open(&text_path).unwrap()).unwrap();
+    let (_, names) =
+        pokemon::build_pokedex(&RomData::new(&rom_path), &text_files, &options).unwrap();
+
+    // Each iteration decodes its GARCs again, like a run does
+    c.bench_function("build_pokedex", |b| {
+        b.iter(|| pokemon::build_pokedex(&RomData::new(&rom_path), &text_files, &options).unwrap())
+    });
+    c.bench_function("build_moves", |b| {
+        b.iter(|| moves::build_moves(&RomData::new(&rom_path), &text_files, &options).unwrap())
+    });
+    c.bench_function("build_learnsets", |b| {
+        b.iter(|| {
+            let rom = RomData::new(&rom_path);
+            learnset::build_learnsets(&rom, &text_files, &names, &options).unwrap()
+        })
+    });
+}
+
+criterion_group!(benches, dump);
+criterion_main!(benches);
@@ -0,0 +1,64 @@
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::{
+    options::Options,
+    pokemon::{PokemonJs, UNUSABLES},
+    to_id,
+};
+
+/// Tier of species without a rule, when `--default-tier` isn't given
+pub const DEFAULT_TIER: &str = "OU";
+
+#[derive(Serialize)]
+pub struct FormatsDataJs {
+    pub tier: String,
+}
+
+/// Megas, Primals and the formes with `battleOnly` only exist during a battle
+fn is_battle_only(poke: &PokemonJs) -> bool {
+    poke.battleOnly.is_some()
+        || poke
+            .forme
+            .as_deref()
+            .is_some_and(|forme| forme.starts_with("Mega") || forme.starts_with("Primal"))
+}
+
+/// Tier of a pokedex entry: not standard ones are Illegal, the box legendaries of
+/// `UNUSABLES` Uber, battle-only formes take their base species' tier and the others
+/// `default_tier`
+pub fn classify_tier(
+    id: &str,
+    dex_map: &IndexMap<String, PokemonJs>,
+    default_tier: &str,
+) -> String {
+    let poke = &dex_map[id];
+    if poke.isNonstandard.is_some() {
+        return "Illegal".to_owned();
+    }
+    if UNUSABLES.contains(&id) {
+        return "Uber".to_owned();
+    }
+    if is_battle_only(poke) {
+        let base_id = poke.baseSpecies.clone().map(to_id);
+        if let Some(base_id) = base_id.filter(|base_id| dex_map.contains_key(base_id)) {
+            return classify_tier(&base_id, dex_map, default_tier);
+        }
+    }
+    default_tier.to_owned()
+}
+
+/// Showdown formats-data of every pokedex entry, removed entries are left out with them
+pub fn build_formats_data(
+    dex_map: &IndexMap<String, PokemonJs>,
+    options: &Options,
+) -> IndexMap<String, FormatsDataJs> {
+    let default_tier = options.default_tier.as_deref().unwrap_or(DEFAULT_TIER);
+    dex_map
+        .keys()
+        .map(|id| {
+            let tier = classify_tier(id, dex_map, default_tier);
+            (id.clone(), FormatsDataJs { tier })
+        })
+        .collect()
+}
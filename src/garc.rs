@@ -1,6 +1,11 @@
-use std::io::Cursor;
+use std::{fs::File, io::Cursor, path::Path};
 
 use binrw::BinRead;
+use color_eyre::{
+    eyre::{bail, eyre, WrapErr},
+    Result,
+};
+use tracing::{debug, info};
 
 #[derive(BinRead, Debug)]
 #[br(magic = b"CRAG")]
@@ -87,26 +92,87 @@ pub struct GarcFile {
     fimb: FileImageBytes,
 }
 
-pub fn _read_file<T: BinRead>(file: usize, subfile: usize, garc: &GarcFile) -> Option<T>
+impl GarcFile {
+    pub fn file_count(&self) -> usize {
+        self.fatb.file_entries.len()
+    }
+}
+
+pub fn open(path: &Path) -> Result<GarcFile> {
+    info!("reading GARC {}", path.display());
+    GarcFile::read_le(&mut File::open(path)?)
+        .wrap_err_with(|| format!("failed to read GARC {}", path.display()))
+}
+
+/// Bytes of a subfile, checked against the image so a corrupt entry is an error instead of
+/// a slice panic
+fn subfile_bytes(garc: &GarcFile, file: usize, subfile: usize) -> Result<Option<&[u8]>> {
+    let Some(sub_entry) = garc.fatb.file_entries[file].entries[subfile] else {
+        return Ok(None);
+    };
+    let (start, end) = (sub_entry.start as usize, sub_entry.end as usize);
+    if end < start || end > garc.fimb.data.len() {
+        bail!(
+            "entry {file} subfile {subfile} has end<start or exceeds data length \
+             (start {start:#x}, end {end:#x}, data length {:#x})",
+            garc.fimb.data.len()
+        );
+    }
+    Ok(Some(&garc.fimb.data[start..end]))
+}
+
+/// Bytes of subfile 0 of entry `file`, undecoded
+pub fn file_bytes(garc: &GarcFile, file: usize) -> Result<&[u8]> {
+    if file >= garc.file_count() {
+        bail!("no entry {file}, the GARC has {}", garc.file_count());
+    }
+    subfile_bytes(garc, file, 0)?.ok_or_else(|| eyre!("entry {file} has no subfile 0"))
+}
+
+/// Writes every subfile as raw `<file>_<subfile>.bin` in `out_dir`, returns how many
+pub fn extract(garc: &GarcFile, out_dir: &Path) -> Result<usize> {
+    std::fs::create_dir_all(out_dir)?;
+    let mut count = 0;
+    for file in 0..garc.file_count() {
+        for subfile in 0..garc.fatb.file_entries[file].entries.len() {
+            let Some(bytes) = subfile_bytes(garc, file, subfile)? else {
+                continue;
+            };
+            std::fs::write(out_dir.join(format!("{file}_{subfile}.bin")), bytes)?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// Decodes one subfile, `None` when it's missing, out of bounds or doesn't parse
+pub fn read_file<T: BinRead>(file: usize, subfile: usize, garc: &GarcFile) -> Option<T>
 where
     for<'a> <T as binrw::BinRead>::Args<'a>: std::default::Default,
 {
-    let file_entry = garc.fatb.file_entries[file].entries[subfile]?;
-    let file_bytes = &garc.fimb.data[file_entry.start as usize..file_entry.end as usize];
+    let file_bytes = subfile_bytes(garc, file, subfile).ok()??;
     T::read_le(&mut Cursor::new(file_bytes)).ok()
 }
 
-pub fn read_files<T: BinRead>(garc: &GarcFile) -> Vec<T>
+pub fn read_files<T: BinRead>(garc: &GarcFile) -> Result<Vec<T>>
 where
     for<'a> <T as binrw::BinRead>::Args<'a>: std::default::Default,
 {
-    garc.fatb
-        .file_entries
-        .iter()
-        .map(|e| e.entries[0].unwrap())
-        .map(|sub_entry| {
-            let file_bytes = &garc.fimb.data[sub_entry.start as usize..sub_entry.end as usize];
-            T::read_le(&mut Cursor::new(file_bytes)).unwrap()
+    read_files_with(garc, |_| Default::default())
+}
+
+/// Like `read_files`, with the import args of each file given by `args_fn(file_index)`
+pub fn read_files_with<'a, T: BinRead>(
+    garc: &GarcFile,
+    mut args_fn: impl FnMut(usize) -> T::Args<'a>,
+) -> Result<Vec<T>> {
+    debug!("decoding {} entries", garc.file_count());
+    (0..garc.file_count())
+        .map(|index| {
+            let file_bytes = subfile_bytes(garc, index, 0)?
+                .ok_or_else(|| eyre!("entry {index} has no subfile 0"))?;
+            T::read_le_args(&mut Cursor::new(file_bytes), args_fn(index))
+                .wrap_err_with(|| format!("failed to parse entry {index} subfile 0"))
         })
         .collect()
 }
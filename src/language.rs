@@ -0,0 +1,103 @@
+use std::fmt;
+
+use crate::{garc_files, text_ids};
+
+/// Text tables read from a text GARC, by their index in it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextIds {
+    pub species_names: usize,
+    pub item_names: usize,
+    pub ability_names: usize,
+    pub ability_descs: usize,
+    pub move_names: usize,
+    pub move_descs: usize,
+    pub type_names: usize,
+}
+
+impl TextIds {
+    /// Indices of the English text GARC, the one `text_ids` was mapped from
+    pub const ENGLISH: TextIds = TextIds {
+        species_names: text_ids::SPECIES_NAMES,
+        item_names: text_ids::ITEM_NAMES,
+        ability_names: text_ids::ABILITY_NAMES,
+        ability_descs: text_ids::ABILITY_DESCS,
+        move_names: text_ids::MOVE_NAMES,
+        move_descs: text_ids::MOVE_DESCS,
+        type_names: text_ids::TYPE_NAMES,
+    };
+}
+
+/// A game language, which has its own text GARC
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Japanese,
+    English,
+    French,
+    Italian,
+    German,
+    Spanish,
+    Korean,
+    ChineseSimplified,
+    ChineseTraditional,
+}
+
+impl Language {
+    pub const ALL: [Language; 9] = [
+        Language::Japanese,
+        Language::English,
+        Language::French,
+        Language::Italian,
+        Language::German,
+        Language::Spanish,
+        Language::Korean,
+        Language::ChineseSimplified,
+        Language::ChineseTraditional,
+    ];
+
+    /// Code used in `--languages` and file names
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::Japanese => "ja",
+            Language::English => "en",
+            Language::French => "fr",
+            Language::Italian => "it",
+            Language::German => "de",
+            Language::Spanish => "es",
+            Language::Korean => "ko",
+            Language::ChineseSimplified => "zh-hans",
+            Language::ChineseTraditional => "zh-hant",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Language> {
+        Self::ALL
+            .into_iter()
+            .find(|language| language.code() == code)
+    }
+
+    pub fn text_garc(self) -> &'static str {
+        match self {
+            Language::Japanese => "0/3/1",
+            Language::English => garc_files::TEXT,
+            Language::French => "0/3/3",
+            Language::Italian => "0/3/4",
+            Language::German => "0/3/5",
+            Language::Spanish => "0/3/6",
+            Language::Korean => "0/3/7",
+            Language::ChineseSimplified => "0/3/8",
+            Language::ChineseTraditional => "0/3/9",
+        }
+    }
+
+    /// The text GARCs of every language are assumed to share the English layout, a
+    /// language found to differ gets its own profile here
+    pub fn text_ids(self) -> &'static TextIds {
+        &TextIds::ENGLISH
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
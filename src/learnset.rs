@@ -1,17 +1,21 @@
 use crate::{
-    garc::{self, GarcFile},
-    garc_files, pokemon,
+    garc, garc_files,
+    language::Language,
+    moves,
+    options::{LearnsetFormat, Options},
+    pokemon,
     text::TextFile,
-    text_ids, to_id, PokemonStats,
+    to_id, write_output, PokemonStats, RomData, BEACH_BITS_LEN, TM_BITS_LEN,
 };
 use binrw::{until_eof, BinRead};
-use color_eyre::Result;
+use color_eyre::{eyre::bail, Result};
 use indexmap::IndexMap;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::fs::File;
-use std::io::Write;
+use std::io::BufReader;
 use std::path::Path;
+use tracing::{info, warn};
 
 #[derive(BinRead, Debug)]
 struct LevelUpMove {
@@ -20,14 +24,14 @@ struct LevelUpMove {
 }
 
 #[derive(BinRead, Debug)]
-struct LevelUpMoves {
+pub(crate) struct LevelUpMoves {
     #[br(parse_with = until_eof)]
     lvl_moves: Vec<LevelUpMove>,
 }
 
 #[allow(non_camel_case_types)]
-#[derive(Serialize)]
-enum Method {
+#[derive(Serialize, Deserialize)]
+pub enum Method {
     lvl,
     tm,
     tutor,
@@ -35,59 +39,150 @@ enum Method {
 }
 
 #[serde_with::skip_serializing_none]
-#[derive(Serialize)]
-struct LearnsetEntry {
+#[derive(Serialize, Deserialize)]
+pub struct LearnsetEntry {
     #[serde[rename = "move"]]
-    move_: String,
-    how: Method,
-    level: Option<i16>,
+    pub move_: String,
+    pub how: Method,
+    pub level: Option<i16>,
+    /// Move number, 0 when read back from a list learnset
+    #[serde(skip)]
+    pub num: u16,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct Learnset(pub Vec<LearnsetEntry>);
+
 #[derive(Serialize)]
-struct Learnset(Vec<LearnsetEntry>);
+pub struct ShowdownLearnset {
+    pub learnset: IndexMap<String, Vec<String>>,
+}
 
 impl Learnset {
     fn merge(mut self, mut other: Learnset) -> Self {
         self.0.append(&mut other.0);
         self
     }
+
+    /// Sources in `gen`, and with `sources_from` the same source in every earlier
+    /// generation down to it where the move existed
+    pub fn to_showdown(&self, gen: u8, sources_from: Option<u8>) -> ShowdownLearnset {
+        let mut learnset: IndexMap<String, Vec<String>> = IndexMap::new();
+        for entry in &self.0 {
+            let source = match (&entry.how, entry.level) {
+                (Method::lvl, Some(level)) => format!("L{level}"),
+                (Method::lvl, None) => "L1".to_owned(),
+                (Method::tm, _) => "M".to_owned(),
+                (Method::tutor, _) => "T".to_owned(),
+                (Method::_egg, _) => "E".to_owned(),
+            };
+            let first_gen = match (sources_from, moves::move_gen(entry.num)) {
+                (Some(from), Some(introduced)) => from.max(introduced).min(gen),
+                _ => gen,
+            };
+            let sources = learnset.entry(entry.move_.clone()).or_default();
+            for gen in (first_gen..=gen).rev() {
+                sources.push(format!("{gen}{source}"));
+            }
+        }
+        ShowdownLearnset { learnset }
+    }
 }
 
-pub fn dump_learnsets(
-    rom_path: &Path,
-    out_path: &Path,
-    text_files: &[TextFile],
-    poke_names: &BTreeMap<usize, String>,
-) -> Result<()> {
-    let move_names = &text_files[text_ids::MOVE_NAMES].lines;
+pub(crate) fn read_level_ups(rom_path: &Path) -> Result<Vec<LevelUpMoves>> {
     let lvl_path = rom_path
         .join(garc_files::BASE_PATH)
         .join(garc_files::LVL_UP_MOVES);
-    let lvl_ups = garc::read_files::<LevelUpMoves>(&GarcFile::read_le(&mut File::open(lvl_path)?)?);
+    garc::read_files(&garc::open(&lvl_path)?)
+}
 
-    let pokemon_path = rom_path
-        .join(garc_files::BASE_PATH)
-        .join(garc_files::POKEMON_STATS);
-    let pokemons =
-        garc::read_files::<PokemonStats>(&GarcFile::read_le(&mut File::open(pokemon_path)?)?);
+/// Learnsets by species id, for the personal data indexes in `poke_names`
+pub fn build_learnsets(
+    rom: &RomData,
+    text_files: &[TextFile],
+    poke_names: &BTreeMap<usize, String>,
+    options: &Options,
+) -> Result<IndexMap<String, Learnset>> {
+    let move_names = &text_files[options.text_ids().move_names].lines;
+    let pokemons = rom.pokemons()?;
+    let lvl_ups = rom.level_ups()?;
+
+    // TMS is by English name, the English move names give their numbers in every language.
+    // The numbers are only needed for the earlier-gen sources, other languages only read
+    // the English text GARC for them.
+    let needs_tm_nums = options.learnset_sources_from.is_some();
+    let english_move_names = match options.language {
+        None | Some(Language::English) => Some(move_names.as_slice()),
+        Some(_) if needs_tm_nums => Some(rom.english_move_names(options)?),
+        Some(_) => None,
+    };
+    let tm_nums: Vec<Option<u16>> = TMS
+        .iter()
+        .map(|name| {
+            let num = english_move_names?.iter().position(|n| n == name)?;
+            Some(num as u16)
+        })
+        .collect();
     let mut learnset_map: IndexMap<String, Learnset> = lvl_ups
         .iter()
         .enumerate()
         .filter(|(index, _)| poke_names.contains_key(index))
         .map(|(index, lvl_ups)| {
-            (
+            Ok((
                 to_id(poke_names[&index].to_owned()),
                 make_lvl_up_learnset(lvl_ups, move_names)
-                    .merge(make_tm_learnset(&pokemons[index], move_names))
+                    .merge(make_tm_learnset(&pokemons[index], &tm_nums, needs_tm_nums)?)
                     .merge(make_beach_learnset(&pokemons[index], move_names))
                     .merge(make_tutor_learnset(&pokemons[index], move_names)),
-            )
+            ))
         })
-        .collect();
-    manual_patches(&mut learnset_map);
-    let mut f = File::create(out_path.join("learnsets.json"))?;
-    write!(f, "{}", serde_json::to_string_pretty(&learnset_map)?)?;
-    Ok(())
+        .collect::<Result<_>>()?;
+    info!("applying learnset manual patches");
+    manual_patches(&mut learnset_map, options);
+
+    let unlisted_tms = pokemons
+        .iter()
+        .filter(|pokemon| count_bits_from(&pokemon.tm_bits, TMS.len()) > 0)
+        .count();
+    if unlisted_tms > 0 {
+        warn!(
+            "{unlisted_tms} species have tm_bits set past the {} listed TMs, TMS is likely missing entries",
+            TMS.len()
+        );
+    }
+    let unlisted_beach_tutors = pokemons
+        .iter()
+        .filter(|pokemon| count_bits_from(&pokemon.beach_bits, BEACH_TUTORS.len()) > 0)
+        .count();
+    if unlisted_beach_tutors > 0 {
+        warn!(
+            "{unlisted_beach_tutors} species have beach_bits set past the {} listed tutors, BEACH_TUTORS is likely missing entries",
+            BEACH_TUTORS.len()
+        );
+    }
+
+    Ok(learnset_map)
+}
+
+/// Writes learnsets.json in the `--learnset-format` format
+pub fn write_learnsets(
+    out_path: &Path,
+    learnset_map: &IndexMap<String, Learnset>,
+    options: &Options,
+) -> Result<()> {
+    match options.learnset_format {
+        LearnsetFormat::List => write_output(out_path, "learnsets", learnset_map, options),
+        LearnsetFormat::Showdown => {
+            let gen = options.rom_type.gen_digit();
+            let showdown_map: IndexMap<&String, ShowdownLearnset> = learnset_map
+                .iter()
+                .map(|(id, learnset)| {
+                    (id, learnset.to_showdown(gen, options.learnset_sources_from))
+                })
+                .collect();
+            write_output(out_path, "learnsets", &showdown_map, options)
+        }
+    }
 }
 
 fn make_lvl_up_learnset(lvl_ups: &LevelUpMoves, move_names: &[String]) -> Learnset {
@@ -100,6 +195,7 @@ fn make_lvl_up_learnset(lvl_ups: &LevelUpMoves, move_names: &[String]) -> Learns
                 move_: to_id(move_names[lvl_up.move_id as usize].to_owned()),
                 how: Method::lvl,
                 level: Some(lvl_up.level),
+                num: lvl_up.move_id as u16,
             })
             .collect(),
     )
@@ -208,6 +304,19 @@ const TMS: &[&str] = &[
     "Protect",
 ];
 
+// check_bit would read past tm_bits otherwise
+const _: () = assert!(TMS.len() <= TM_BITS_LEN * 8);
+
+/// Writes the TM number to move id mapping of `TMS`
+pub fn dump_tms(out_path: &Path, options: &Options) -> Result<()> {
+    let tm_map: IndexMap<String, String> = TMS
+        .iter()
+        .enumerate()
+        .map(|(index, name)| (format!("TM{:02}", index + 1), to_id(name.to_string())))
+        .collect();
+    write_output(out_path, "tms", &tm_map, options)
+}
+
 fn check_bit(bits: &[u8], index: usize) -> bool {
     let byte = index / 8;
     let bit = 1 << (index % 8);
@@ -215,20 +324,40 @@ fn check_bit(bits: &[u8], index: usize) -> bool {
     bits[byte] & bit != 0
 }
 
-fn make_tm_learnset(pokemon: &PokemonStats, _move_names: &[String]) -> Learnset {
-    Learnset(
-        TMS.iter()
-            .enumerate()
-            .filter_map(|(index, name)| match check_bit(&pokemon.tm_bits, index) {
-                true => Some(LearnsetEntry {
-                    move_: to_id(name.to_string()),
-                    how: Method::tm,
-                    level: None,
-                }),
-                false => None,
-            })
-            .collect(),
-    )
+/// Number of bits set at or after `start`, bits are read least significant first
+fn count_bits_from(bits: &[u8], start: usize) -> usize {
+    (start..bits.len() * 8)
+        .filter(|index| check_bit(bits, *index))
+        .count()
+}
+
+/// A TM without a number gets no earlier-gen sources, unless `needs_nums` where it fails
+/// rather than silently losing them
+fn make_tm_learnset(
+    pokemon: &PokemonStats,
+    tm_nums: &[Option<u16>],
+    needs_nums: bool,
+) -> Result<Learnset> {
+    let mut entries = Vec::new();
+    for (index, name) in TMS.iter().enumerate() {
+        if !check_bit(&pokemon.tm_bits, index) {
+            continue;
+        }
+        let num = match tm_nums[index] {
+            Some(num) => num,
+            None if needs_nums => {
+                bail!("TM{:02} {name} isn't in the English move names", index + 1)
+            }
+            None => 0,
+        };
+        entries.push(LearnsetEntry {
+            move_: to_id(name.to_string()),
+            how: Method::tm,
+            level: None,
+            num,
+        });
+    }
+    Ok(Learnset(entries))
 }
 
 #[allow(clippy::zero_prefixed_literal)]
@@ -238,6 +367,7 @@ const BEACH_TUTORS: &[u16] = &[
     366, 143, 220, 202, 409, 264, 351, 352, 380, 388, 180, 495, 270, 271, 478, 472, 283, 200, 278,
     289, 446, 285, 477, 502, 432, 710, 707, 675, 673,
 ];
+const _: () = assert!(BEACH_TUTORS.len() <= BEACH_BITS_LEN * 8);
 
 fn make_beach_learnset(pokemon: &PokemonStats, move_names: &[String]) -> Learnset {
     Learnset(
@@ -250,6 +380,7 @@ fn make_beach_learnset(pokemon: &PokemonStats, move_names: &[String]) -> Learnse
                         move_: to_id(move_names[*move_id as usize].to_owned()),
                         how: Method::tutor,
                         level: None,
+                        num: *move_id,
                     }),
                     false => None,
                 },
@@ -270,6 +401,7 @@ fn make_tutor_learnset(pokemon: &PokemonStats, move_names: &[String]) -> Learnse
                         move_: to_id(move_names[*move_id as usize].to_owned()),
                         how: Method::tutor,
                         level: None,
+                        num: *move_id,
                     }),
                     false => None,
                 },
@@ -278,7 +410,7 @@ fn make_tutor_learnset(pokemon: &PokemonStats, move_names: &[String]) -> Learnse
     )
 }
 
-fn manual_patches(learnset_map: &mut IndexMap<String, Learnset>) {
+fn manual_patches(learnset_map: &mut IndexMap<String, Learnset>, options: &Options) {
     const COMBAT_FORMES: &[&str] = &[
         "minior",
         "venusaurmega",
@@ -342,11 +474,18 @@ fn manual_patches(learnset_map: &mut IndexMap<String, Learnset>) {
         "castformrainy",
     ];
 
-    for combat_forme in COMBAT_FORMES {
-        learnset_map.shift_remove(*combat_forme);
+    // Flattened formes keep their own learnset instead of using the base one
+    if !options.flatten_formes {
+        for combat_forme in COMBAT_FORMES {
+            learnset_map.shift_remove(*combat_forme);
+        }
     }
 
     for remove in pokemon::UNUSABLES {
         learnset_map.shift_remove(*remove);
     }
 }
+
+pub fn load_learnsets(path: &Path) -> Result<IndexMap<String, Learnset>> {
+    Ok(serde_json::from_reader(BufReader::new(File::open(path)?))?)
+}
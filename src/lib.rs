@@ -0,0 +1,750 @@
+// binrw converts `count` arguments with `TryInto`, which clippy flags for u16 counts.
+#![allow(clippy::unnecessary_fallible_conversions)]
+
+use std::{
+    cell::OnceCell,
+    collections::BTreeMap,
+    fs::File,
+    io::{BufReader, Write},
+    path::{Path, PathBuf},
+};
+
+use binrw::BinRead;
+use color_eyre::{eyre::ensure, Result};
+use indexmap::IndexMap;
+use language::{Language, TextIds};
+use options::{LearnsetFormat, Options, OutputFormat};
+use serde::{Deserialize, Serialize};
+use text::TextFile;
+use tracing::info;
+
+pub mod garc;
+pub mod language;
+pub mod text;
+
+pub mod formats;
+pub mod learnset;
+pub mod moves;
+pub mod pokemon;
+
+pub mod manifest;
+pub mod options;
+pub mod validate;
+
+pub use learnset::load_learnsets;
+pub use moves::load_moves;
+pub use pokemon::load_pokedex;
+
+pub mod text_ids {
+    pub const SPECIES_NAMES: usize = 60;
+
+    pub const ITEM_NAMES: usize = 40;
+    pub const _ITEM_DESCS: usize = 39;
+
+    pub const ABILITY_NAMES: usize = 101;
+    pub const ABILITY_DESCS: usize = 102;
+
+    pub const MOVE_NAMES: usize = 118;
+    pub const MOVE_DESCS: usize = 117;
+
+    pub const TYPE_NAMES: usize = 112;
+
+    /// Name tables written by `--emit-names`
+    pub const NAMES: &[(&str, usize)] = &[
+        ("SPECIES_NAMES", SPECIES_NAMES),
+        ("ITEM_NAMES", ITEM_NAMES),
+        ("ABILITY_NAMES", ABILITY_NAMES),
+        ("MOVE_NAMES", MOVE_NAMES),
+        ("TYPE_NAMES", TYPE_NAMES),
+    ];
+}
+
+pub mod garc_files {
+    pub const BASE_PATH: &str = "romfs/a/";
+
+    pub const MOVE: &str = "0/1/1";
+    pub const EGG_MOVES: &str = "0/1/2";
+    pub const LVL_UP_MOVES: &str = "0/1/3";
+
+    pub const EVOLUTIONS: &str = "0/1/4";
+    pub const MEGA_EVOS: &str = "0/1/5";
+
+    pub const POKEMON_STATS: &str = "0/1/7";
+
+    pub const TEXT: &str = "0/3/2";
+
+    /// GARCs each output is built from, `TEXT` stands for the text GARC of the language
+    pub const OUTPUT_INPUTS: &[(&str, &[&str])] = &[
+        ("pokedex", &[POKEMON_STATS, EVOLUTIONS, MEGA_EVOS, TEXT]),
+        ("learnsets", &[LVL_UP_MOVES, POKEMON_STATS, TEXT]),
+        ("moves", &[MOVE, TEXT]),
+        ("abilities", &[TEXT]),
+    ];
+
+    pub const ALL: &[(&str, &str)] = &[
+        ("MOVE", MOVE),
+        ("EGG_MOVES", EGG_MOVES),
+        ("LVL_UP_MOVES", LVL_UP_MOVES),
+        ("EVOLUTIONS", EVOLUTIONS),
+        ("MEGA_EVOS", MEGA_EVOS),
+        ("POKEMON_STATS", POKEMON_STATS),
+        ("TEXT", TEXT),
+    ];
+}
+
+#[derive(BinRead, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Stats {
+    pub hp: u8,
+    pub atk: u8,
+    pub def: u8,
+    pub spe: u8,
+    pub spa: u8,
+    pub spd: u8,
+}
+
+impl Stats {
+    /// Stat ids in ROM order, which is also the order of `ev_yield` and `Index`
+    pub const IDS: [&'static str; 6] = ["hp", "atk", "def", "spe", "spa", "spd"];
+
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, u8)> + '_ {
+        Self::IDS.iter().enumerate().map(|(i, id)| (*id, self[i]))
+    }
+}
+
+impl std::ops::Index<usize> for Stats {
+    type Output = u8;
+
+    fn index(&self, index: usize) -> &u8 {
+        match index {
+            0 => &self.hp,
+            1 => &self.atk,
+            2 => &self.def,
+            3 => &self.spe,
+            4 => &self.spa,
+            5 => &self.spd,
+            _ => panic!("stat index {index} out of range"),
+        }
+    }
+}
+
+/// Gen 3+ HP formula, Shedinja's base 1 HP always stays at 1
+pub fn calc_hp(base: u8, iv: u8, ev: u8, level: u8) -> u16 {
+    if base == 1 {
+        return 1;
+    }
+    let (base, iv, ev, level) = (base as u32, iv as u32, ev as u32, level as u32);
+    ((2 * base + iv + ev / 4) * level / 100 + level + 10) as u16
+}
+
+/// Gen 3+ formula for every stat but HP, `nature_mult` is 0.9, 1.0 or 1.1
+pub fn calc_stat(base: u8, iv: u8, ev: u8, level: u8, nature_mult: f32) -> u16 {
+    let (base, iv, ev, level) = (base as u32, iv as u32, ev as u32, level as u32);
+    let raw = (2 * base + iv + ev / 4) * level / 100 + 5;
+    // Multiply in tenths so 0.9 doesn't round down a whole point
+    let nature_tenths = (nature_mult * 10.).round() as u32;
+    (raw * nature_tenths / 10) as u16
+}
+
+const TM_BITS_LEN: usize = 0x10;
+const BEACH_BITS_LEN: usize = 0xa;
+const GENDER_DIFFERENCE_BIT: u8 = 1 << 7;
+
+/// Personal data of a species or forme, as stored in the POKEMON_STATS GARC
+#[derive(BinRead, Debug)]
+pub struct PokemonStats {
+    pub stats: Stats,
+    pub types: (u8, u8),
+    pub catch_rate: u8,
+    pub evo_stage: u8,
+    pub ev_yield: u16,
+    pub items: [u16; 3],
+    pub gender: u8,
+    pub hatch_cycles: u8,
+    pub base_friendship: u8,
+    pub exp_growth: u8,
+    pub egg_groups: [u8; 2],
+    pub abilities: [u8; 3],
+    pub escape_rate: u8,
+    pub form_stats_id: u16,
+    pub form_sprite: u16,
+    pub form_count: u8,
+    /// Low 6 bits are the dex color, bit 7 (`GENDER_DIFFERENCE_BIT`) marks a separate
+    /// female model
+    pub sprite_bits: u8,
+    pub base_exp: u16,
+    pub height: u16,
+    pub weight: u16,
+    pub tm_bits: [u8; TM_BITS_LEN],
+    pub tutor_bits: [u8; 0x4],
+    pub beach_bits: [u8; BEACH_BITS_LEN],
+}
+
+/// Decodes the POKEMON_STATS GARC, indexed by species then `form_stats_id` formes
+pub fn read_pokemon_stats(rom_path: &Path) -> Result<Vec<PokemonStats>> {
+    let path = rom_path
+        .join(garc_files::BASE_PATH)
+        .join(garc_files::POKEMON_STATS);
+    garc::read_files(&garc::open(&path)?)
+}
+
+/// The GARCs every language shares, each decoded on first use so a run over several
+/// languages only reads the text GARC again. The ones no built output needs are never
+/// opened, so `--only` works on a partial romfs.
+pub struct RomData {
+    pub path: PathBuf,
+    pokemons: OnceCell<Vec<PokemonStats>>,
+    evolutions: OnceCell<Vec<pokemon::Evolutions>>,
+    mega_evos: OnceCell<Vec<pokemon::PokemonMegaEvolutions>>,
+    level_ups: OnceCell<Vec<learnset::LevelUpMoves>>,
+    moves: OnceCell<Vec<moves::MoveStats>>,
+    english_move_names: OnceCell<Vec<String>>,
+}
+
+fn get_or_read<T>(cell: &OnceCell<T>, read: impl FnOnce() -> Result<T>) -> Result<&T> {
+    if let Some(value) = cell.get() {
+        return Ok(value);
+    }
+    let value = read()?;
+    Ok(cell.get_or_init(|| value))
+}
+
+impl RomData {
+    pub fn new(path: &Path) -> RomData {
+        RomData {
+            path: path.to_owned(),
+            pokemons: OnceCell::new(),
+            evolutions: OnceCell::new(),
+            mega_evos: OnceCell::new(),
+            level_ups: OnceCell::new(),
+            moves: OnceCell::new(),
+            english_move_names: OnceCell::new(),
+        }
+    }
+
+    pub fn pokemons(&self) -> Result<&[PokemonStats]> {
+        get_or_read(&self.pokemons, || read_pokemon_stats(&self.path)).map(Vec::as_slice)
+    }
+
+    pub(crate) fn evolutions(&self) -> Result<&[pokemon::Evolutions]> {
+        get_or_read(&self.evolutions, || pokemon::read_evolutions(&self.path)).map(Vec::as_slice)
+    }
+
+    pub(crate) fn mega_evos(&self) -> Result<&[pokemon::PokemonMegaEvolutions]> {
+        get_or_read(&self.mega_evos, || pokemon::read_mega_evos(&self.path)).map(Vec::as_slice)
+    }
+
+    pub(crate) fn level_ups(&self) -> Result<&[learnset::LevelUpMoves]> {
+        get_or_read(&self.level_ups, || learnset::read_level_ups(&self.path)).map(Vec::as_slice)
+    }
+
+    pub fn moves(&self) -> Result<&[moves::MoveStats]> {
+        get_or_read(&self.moves, || moves::read_move_stats(&self.path)).map(Vec::as_slice)
+    }
+
+    /// The move names of the English text GARC, for the tables kept by English name
+    pub(crate) fn english_move_names(&self, options: &Options) -> Result<&[String]> {
+        get_or_read(&self.english_move_names, || {
+            let path = self.path.join(garc_files::BASE_PATH).join(garc_files::TEXT);
+            let decoder = options.rom_type.text_decoder();
+            let mut text_files =
+                garc::read_files_with::<TextFile>(&garc::open(&path)?, |_| (decoder,))?;
+            let move_names = TextIds::ENGLISH.move_names;
+            ensure!(
+                move_names < text_files.len(),
+                "the English text GARC has no move names"
+            );
+            Ok(text_files.swap_remove(move_names).lines)
+        })
+        .map(Vec::as_slice)
+    }
+}
+
+/// Column names of raw_stats.csv, the bitfields are written as little-endian hex
+const RAW_STATS_COLUMNS: &[&str] = &[
+    "index",
+    "hp",
+    "atk",
+    "def",
+    "spe",
+    "spa",
+    "spd",
+    "type1",
+    "type2",
+    "catch_rate",
+    "evo_stage",
+    "ev_yield",
+    "item1",
+    "item2",
+    "item3",
+    "gender",
+    "hatch_cycles",
+    "base_friendship",
+    "exp_growth",
+    "egg_group1",
+    "egg_group2",
+    "ability1",
+    "ability2",
+    "ability_hidden",
+    "escape_rate",
+    "form_stats_id",
+    "form_sprite",
+    "form_count",
+    "sprite_bits",
+    "base_exp",
+    "height",
+    "weight",
+    "tm_bits",
+    "tutor_bits",
+    "beach_bits",
+];
+
+fn hex(bits: &[u8]) -> String {
+    bits.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Writes raw_stats.csv, the personal data of every stats index with its numeric ids
+pub fn dump_raw_stats(rom_path: &Path, out_path: &Path, options: &Options) -> Result<()> {
+    let mut csv = RAW_STATS_COLUMNS.join(",") + "\n";
+    for (index, pokemon) in read_pokemon_stats(rom_path)?.iter().enumerate() {
+        let mut row: Vec<String> = vec![index.to_string()];
+        row.extend(pokemon.stats.iter().map(|(_, stat)| stat.to_string()));
+        row.extend([
+            pokemon.types.0.to_string(),
+            pokemon.types.1.to_string(),
+            pokemon.catch_rate.to_string(),
+            pokemon.evo_stage.to_string(),
+            pokemon.ev_yield.to_string(),
+        ]);
+        row.extend(pokemon.items.iter().map(u16::to_string));
+        row.extend([
+            pokemon.gender.to_string(),
+            pokemon.hatch_cycles.to_string(),
+            pokemon.base_friendship.to_string(),
+            pokemon.exp_growth.to_string(),
+        ]);
+        row.extend(pokemon.egg_groups.iter().map(u8::to_string));
+        row.extend(pokemon.abilities.iter().map(u8::to_string));
+        row.extend([
+            pokemon.escape_rate.to_string(),
+            pokemon.form_stats_id.to_string(),
+            pokemon.form_sprite.to_string(),
+            pokemon.form_count.to_string(),
+            pokemon.sprite_bits.to_string(),
+            pokemon.base_exp.to_string(),
+            pokemon.height.to_string(),
+            pokemon.weight.to_string(),
+            hex(&pokemon.tm_bits),
+            hex(&pokemon.tutor_bits),
+            hex(&pokemon.beach_bits),
+        ]);
+        csv += &(row.join(",") + "\n");
+    }
+
+    if options.dry_run {
+        println!("{:<24} {:>10} bytes", "raw_stats.csv", csv.len());
+        return Ok(());
+    }
+    info!("writing raw_stats.csv");
+    std::fs::write(out_path.join("raw_stats.csv"), csv)?;
+    Ok(())
+}
+
+/// Names whose Showdown id isn't just the name stripped down to ascii alphanumerics. The
+/// text decoder already turns Flabébé's accents into plain letters.
+const ID_ALIASES: &[(&str, &str)] = &[("Nidoran♀", "nidoranf"), ("Nidoran♂", "nidoranm")];
+
+pub fn to_id(s: String) -> String {
+    if let Some((_, id)) = ID_ALIASES.iter().find(|(name, _)| *name == s) {
+        return (*id).to_owned();
+    }
+    s.to_lowercase()
+        .chars()
+        .filter(|c| c.is_ascii_lowercase() || c.is_ascii_uppercase() || c.is_ascii_digit())
+        .collect()
+}
+
+/// Export name, table type and module of the Showdown data file for each output
+const SHOWDOWN_TS_TYPES: &[(&str, (&str, &str, &str))] = &[
+    ("pokedex", ("Pokedex", "SpeciesDataTable", "dex-species")),
+    ("moves", ("Moves", "MoveDataTable", "dex-moves")),
+    (
+        "abilities",
+        ("Abilities", "AbilityDataTable", "dex-abilities"),
+    ),
+    (
+        "learnsets",
+        ("Learnsets", "LearnsetDataTable", "dex-species"),
+    ),
+];
+
+/// File name and contents of an output in the selected format
+pub fn serialize_output<T: Serialize>(
+    name: &str,
+    value: &T,
+    options: &Options,
+) -> Result<(String, Vec<u8>)> {
+    let file_name = output_file_name(name, options);
+    #[cfg(feature = "msgpack")]
+    if options.format == OutputFormat::MsgPack {
+        return Ok((file_name, rmp_serde::encode::to_vec_named(value)?));
+    }
+
+    let indent = options.indent.as_deref().unwrap_or("  ");
+    let mut json = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+    let mut serializer = serde_json::Serializer::with_formatter(&mut json, formatter);
+    value.serialize(&mut serializer)?;
+    let json = String::from_utf8(json)?;
+    let ts_types = SHOWDOWN_TS_TYPES.iter().find(|(file, _)| *file == name);
+    match (options.format, ts_types) {
+        (OutputFormat::ShowdownTs, Some((_, (export, table, module)))) => {
+            let mut out = Vec::new();
+            writeln!(out, "import type {{{table}}} from '../sim/{module}';")?;
+            writeln!(out)?;
+            writeln!(out, "export const {export}: {table} = {json};")?;
+            Ok((file_name, out))
+        }
+        _ => Ok((file_name, json.into_bytes())),
+    }
+}
+
+/// Name of the file `serialize_output` produces for the output `name`
+pub fn output_file_name(name: &str, options: &Options) -> String {
+    let stem = match &options.language {
+        Some(language) => format!("{name}.{language}"),
+        None => name.to_owned(),
+    };
+    #[cfg(feature = "msgpack")]
+    if options.format == OutputFormat::MsgPack {
+        return format!("{stem}.msgpack");
+    }
+    let has_ts_types = SHOWDOWN_TS_TYPES.iter().any(|(file, _)| *file == name);
+    match options.format {
+        OutputFormat::ShowdownTs if has_ts_types => format!("{stem}.ts"),
+        _ => format!("{stem}.json"),
+    }
+}
+
+/// Every output name the tool writes, before the language code and extension
+pub const OUTPUT_NAMES: &[&str] = &[
+    "pokedex",
+    "learnsets",
+    "moves",
+    "moves-physical",
+    "moves-special",
+    "moves-status",
+    "abilities",
+    "ability_ids",
+    "names",
+    "species-full",
+    "formats-data",
+    "tms",
+    "manifest",
+    "meta",
+    "raw_stats",
+    "build-options",
+];
+
+/// Whether `file_name` looks like one of our outputs, e.g. `pokedex.fr.json`
+pub fn is_output_file(file_name: &str) -> bool {
+    let Some((stem, extension)) = file_name.rsplit_once('.') else {
+        return false;
+    };
+    let name = stem.split_once('.').map_or(stem, |(name, _)| name);
+    OUTPUT_NAMES.contains(&name) && ["json", "ts", "msgpack", "csv"].contains(&extension)
+}
+
+/// File recording the options each output in the directory was written with
+const BUILD_OPTIONS_FILE: &str = "build-options.json";
+
+/// The options that change the contents of an output
+fn build_options(options: &Options) -> String {
+    format!(
+        "format={:?} learnset-format={:?} flatten-formes={} compact-formes={} indent={:?}",
+        options.format,
+        options.learnset_format,
+        options.flatten_formes,
+        options.compact_formes,
+        options.indent.as_deref().unwrap_or("  "),
+    )
+}
+
+fn read_build_options(out_path: &Path) -> IndexMap<String, String> {
+    File::open(out_path.join(BUILD_OPTIONS_FILE))
+        .ok()
+        .and_then(|file| serde_json::from_reader(BufReader::new(file)).ok())
+        .unwrap_or_default()
+}
+
+/// Records the options `file_name` was written with, for `is_up_to_date`
+fn record_build_options(out_path: &Path, file_name: &str, options: &Options) -> Result<()> {
+    let mut recorded = read_build_options(out_path);
+    recorded.insert(file_name.to_owned(), build_options(options));
+    let json = serde_json::to_vec_pretty(&recorded)?;
+    std::fs::write(out_path.join(BUILD_OPTIONS_FILE), json)?;
+    Ok(())
+}
+
+/// Whether the output `name` is newer than every GARC it's built from and was written with
+/// the same options, in which case it isn't regenerated unless `--rebuild` is given
+pub fn is_up_to_date(rom_path: &Path, out_path: &Path, name: &str, options: &Options) -> bool {
+    if options.rebuild || options.dry_run {
+        return false;
+    }
+    let Some((_, inputs)) = garc_files::OUTPUT_INPUTS.iter().find(|(n, _)| *n == name) else {
+        return false;
+    };
+    let file_name = output_file_name(name, options);
+    if read_build_options(out_path).get(&file_name) != Some(&build_options(options)) {
+        return false;
+    }
+    let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    let Some(output_time) = modified(&out_path.join(file_name)) else {
+        return false;
+    };
+    let text_garc = options
+        .language
+        .map_or(garc_files::TEXT, Language::text_garc);
+    inputs
+        .iter()
+        .map(|input| match *input {
+            garc_files::TEXT => text_garc,
+            input => input,
+        })
+        .all(|input| {
+            modified(&rom_path.join(garc_files::BASE_PATH).join(input))
+                .is_some_and(|input_time| input_time <= output_time)
+        })
+}
+
+/// Writes an output to `out_path`, or only prints its size with `--dry-run`
+pub fn write_output<T: Serialize>(
+    out_path: &Path,
+    name: &str,
+    value: &T,
+    options: &Options,
+) -> Result<()> {
+    let (file_name, contents) = serialize_output(name, value, options)?;
+    if options.dry_run {
+        println!("{file_name:<24} {:>10} bytes", contents.len());
+        return Ok(());
+    }
+    info!("writing {file_name}");
+    std::fs::write(out_path.join(&file_name), contents)?;
+    if garc_files::OUTPUT_INPUTS.iter().any(|(n, _)| *n == name) {
+        record_build_options(out_path, &file_name, options)?;
+    }
+    Ok(())
+}
+
+/// Writes names.json, every name table as an array indexed like the ROM
+pub fn dump_names(out_path: &Path, text_files: &[TextFile], options: &Options) -> Result<()> {
+    let names: IndexMap<&str, &Vec<String>> = text_ids::NAMES
+        .iter()
+        .map(|(name, id)| (*name, &text_files[*id].lines))
+        .collect();
+    write_output(out_path, "names", &names, options)
+}
+
+/// First sentence of an in-game description on a single line, for `shortDesc`
+pub fn summarize(desc: &str) -> String {
+    let line = desc.split_whitespace().collect::<Vec<_>>().join(" ");
+    match line.find(". ") {
+        Some(end) => line[..=end].to_owned(),
+        None => line,
+    }
+}
+
+#[allow(non_snake_case)]
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AbilityJs {
+    pub name: String,
+    pub num: u32,
+    pub desc: String,
+    pub shortDesc: String,
+    pub isNonstandard: Option<String>,
+    /// Only with `--ability-flags`, these games predate the field
+    pub flags: Option<BTreeMap<String, u8>>,
+}
+
+/// Neuroforce, abilities past it were introduced after Gen 7
+const LAST_GEN7_ABILITY: usize = 233;
+
+/// Showdown ability flags of the Gen 7 abilities. `breakable` ones are ignored by Mold
+/// Breaker, `cantsuppress` ones by Gastro Acid.
+const ABILITY_FLAGS: &[(&str, &[&str])] = &[
+    ("aromaveil", &["breakable"]),
+    ("battlearmor", &["breakable"]),
+    ("battlebond", &["cantsuppress"]),
+    ("bigpecks", &["breakable"]),
+    ("bulletproof", &["breakable"]),
+    ("clearbody", &["breakable"]),
+    ("comatose", &["cantsuppress"]),
+    ("contrary", &["breakable"]),
+    ("damp", &["breakable"]),
+    ("dazzling", &["breakable"]),
+    ("disguise", &["cantsuppress", "breakable"]),
+    ("dryskin", &["breakable"]),
+    ("filter", &["breakable"]),
+    ("flashfire", &["breakable"]),
+    ("flowergift", &["breakable"]),
+    ("flowerveil", &["breakable"]),
+    ("fluffy", &["breakable"]),
+    ("friendguard", &["breakable"]),
+    ("furcoat", &["breakable"]),
+    ("grasspelt", &["breakable"]),
+    ("heatproof", &["breakable"]),
+    ("heavymetal", &["breakable"]),
+    ("hypercutter", &["breakable"]),
+    ("immunity", &["breakable"]),
+    ("innerfocus", &["breakable"]),
+    ("insomnia", &["breakable"]),
+    ("keeneye", &["breakable"]),
+    ("leafguard", &["breakable"]),
+    ("levitate", &["breakable"]),
+    ("lightmetal", &["breakable"]),
+    ("lightningrod", &["breakable"]),
+    ("limber", &["breakable"]),
+    ("magicbounce", &["breakable"]),
+    ("magmaarmor", &["breakable"]),
+    ("marvelscale", &["breakable"]),
+    ("motordrive", &["breakable"]),
+    ("multiscale", &["breakable"]),
+    ("multitype", &["cantsuppress"]),
+    ("oblivious", &["breakable"]),
+    ("overcoat", &["breakable"]),
+    ("owntempo", &["breakable"]),
+    ("powerconstruct", &["cantsuppress"]),
+    ("queenlymajesty", &["breakable"]),
+    ("rkssystem", &["cantsuppress"]),
+    ("sapsipper", &["breakable"]),
+    ("schooling", &["cantsuppress"]),
+    ("shellarmor", &["breakable"]),
+    ("shielddust", &["breakable"]),
+    ("shieldsdown", &["cantsuppress"]),
+    ("simple", &["breakable"]),
+    ("snowcloak", &["breakable"]),
+    ("solidrock", &["breakable"]),
+    ("soundproof", &["breakable"]),
+    ("stancechange", &["cantsuppress"]),
+    ("stickyhold", &["breakable"]),
+    ("stormdrain", &["breakable"]),
+    ("sturdy", &["breakable"]),
+    ("suctioncups", &["breakable"]),
+    ("sweetveil", &["breakable"]),
+    ("tangledfeet", &["breakable"]),
+    ("telepathy", &["breakable"]),
+    ("thickfat", &["breakable"]),
+    ("unaware", &["breakable"]),
+    ("vitalspirit", &["breakable"]),
+    ("voltabsorb", &["breakable"]),
+    ("waterabsorb", &["breakable"]),
+    ("waterbubble", &["breakable"]),
+    ("waterveil", &["breakable"]),
+    ("whitesmoke", &["breakable"]),
+    ("wonderguard", &["breakable"]),
+    ("wonderskin", &["breakable"]),
+    ("zenmode", &["cantsuppress"]),
+];
+
+fn ability_flags(id: &str) -> BTreeMap<String, u8> {
+    ABILITY_FLAGS
+        .iter()
+        .find(|(ability, _)| *ability == id)
+        .map(|(_, flags)| flags.iter().map(|flag| ((*flag).to_owned(), 1)).collect())
+        .unwrap_or_default()
+}
+
+pub fn dump_abilities(
+    _rom_path: &Path,
+    out_path: &Path,
+    text_files: &[TextFile],
+    options: &Options,
+) -> Result<()> {
+    let ability_map = build_abilities(text_files, options)?;
+    write_output(out_path, "abilities", &ability_map, options)
+}
+
+/// Abilities by id, from their names and descriptions
+pub fn build_abilities(
+    text_files: &[TextFile],
+    options: &Options,
+) -> Result<IndexMap<String, AbilityJs>> {
+    let ids = options.text_ids();
+    let ability_names = &text_files[ids.ability_names].lines;
+    let ability_descs: Vec<String> = text_files[ids.ability_descs]
+        .lines
+        .iter()
+        .map(|line| text::single_line(line))
+        .collect();
+    ensure!(
+        ability_descs.len() >= ability_names.len(),
+        "text file {} has {} lines for {} abilities, the ability_descs text id is likely wrong",
+        ids.ability_descs,
+        ability_descs.len(),
+        ability_names.len()
+    );
+
+    let ability_map: IndexMap<String, AbilityJs> = ability_names
+        .iter()
+        .enumerate()
+        .map(|(index, name)| {
+            let id = to_id(name.clone());
+            let flags = options.ability_flags.then(|| ability_flags(&id));
+            (
+                id,
+                AbilityJs {
+                    name: name.clone(),
+                    num: index as _,
+                    desc: ability_descs[index].clone(),
+                    shortDesc: summarize(&ability_descs[index]),
+                    isNonstandard: (index > LAST_GEN7_ABILITY).then(|| "Future".to_owned()),
+                    flags,
+                },
+            )
+        })
+        .skip(options.skipped_slots())
+        .collect();
+    Ok(ability_map)
+}
+
+/// Each pokedex entry with its learnset under `learnset`, in the `--learnset-format` format
+pub fn species_full(
+    dex_map: &IndexMap<String, pokemon::PokemonJs>,
+    learnset_map: &IndexMap<String, learnset::Learnset>,
+    options: &Options,
+) -> Result<IndexMap<String, serde_json::Value>> {
+    let gen = options.rom_type.gen_digit();
+    let mut species = IndexMap::new();
+    for (id, dex) in dex_map {
+        let mut entry = serde_json::to_value(dex)?;
+        if let (Some(learnset), Some(fields)) = (learnset_map.get(id), entry.as_object_mut()) {
+            let learnset = match options.learnset_format {
+                LearnsetFormat::List => serde_json::to_value(learnset)?,
+                LearnsetFormat::Showdown => {
+                    let learnset = learnset.to_showdown(gen, options.learnset_sources_from);
+                    serde_json::to_value(learnset.learnset)?
+                }
+            };
+            fields.insert("learnset".to_owned(), learnset);
+        }
+        species.insert(id.clone(), entry);
+    }
+    Ok(species)
+}
+
+/// Writes ability_ids.json, ability name to ROM id, slot 0 included
+pub fn dump_ability_ids(out_path: &Path, text_files: &[TextFile], options: &Options) -> Result<()> {
+    let ability_ids: IndexMap<&String, usize> = text_files[options.text_ids().ability_names]
+        .lines
+        .iter()
+        .enumerate()
+        .map(|(index, name)| (name, index))
+        .collect();
+    write_output(out_path, "ability_ids", &ability_ids, options)
+}
+
+pub fn load_abilities(path: &Path) -> Result<IndexMap<String, AbilityJs>> {
+    Ok(serde_json::from_reader(BufReader::new(File::open(path)?))?)
+}
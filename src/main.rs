@@ -1,139 +1,212 @@
-use std::{env, fs::File, io::Write, path::Path};
-
-use binrw::BinRead;
-use color_eyre::Result;
-use indexmap::IndexMap;
-use serde::Serialize;
-use text::TextFile;
-
-use crate::garc::GarcFile;
-
-mod garc;
-mod text;
-
-mod learnset;
-mod moves;
-mod pokemon;
-
-mod text_ids {
-    pub const SPECIES_NAMES: usize = 60;
-
-    pub const ITEM_NAMES: usize = 40;
-    pub const _ITEM_DESCS: usize = 39;
-
-    pub const ABILITY_NAMES: usize = 101;
-    pub const ABILITY_DESCS: usize = 102;
-
-    pub const MOVE_NAMES: usize = 118;
-    pub const MOVE_DESCS: usize = 117;
-
-    pub const TYPE_NAMES: usize = 112;
-}
-
-mod garc_files {
-    pub const BASE_PATH: &str = "romfs/a/";
-
-    pub const MOVE: &str = "0/1/1";
-    pub const _EGG_MOVES: &str = "0/1/2";
-    pub const LVL_UP_MOVES: &str = "0/1/3";
-
-    pub const EVOLUTIONS: &str = "0/1/4";
-    pub const MEGA_EVOS: &str = "0/1/5";
-
-    pub const POKEMON_STATS: &str = "0/1/7";
-}
-
-#[allow(dead_code)]
-#[derive(BinRead, Serialize, Debug, Clone)]
-struct Stats {
-    hp: u8,
-    atk: u8,
-    def: u8,
-    spe: u8,
-    spa: u8,
-    spd: u8,
-}
-#[allow(dead_code)]
-#[derive(BinRead, Debug)]
-struct PokemonStats {
-    stats: Stats,
-    types: (u8, u8),
-    catch_rate: u8,
-    evo_stage: u8,
-    ev_yield: u16,
-    items: [u16; 3],
-    gender: u8,
-    hatch_cycles: u8,
-    base_friendship: u8,
-    exp_growth: u8,
-    egg_groups: [u8; 2],
-    abilities: [u8; 3],
-    escape_rate: u8,
-    form_stats_id: u16,
-    form_sprite: u16,
-    form_count: u8,
-    sprite_bits: u8,
-    base_exp: u16,
-    height: u16,
-    weight: u16,
-    tm_bits: [u8; 0x10],
-    tutor_bits: [u8; 0x4],
-    beach_bits: [u8; 0xa],
+use std::{env, path::Path, process};
+
+use color_eyre::{eyre::WrapErr, Report, Result};
+use usum_extractor::{
+    dump_abilities, dump_ability_ids, dump_names, dump_raw_stats, formats, garc, garc_files,
+    is_up_to_date, learnset, manifest, moves, options::Options, pokemon, species_full, text,
+    text_ids, validate, write_output, RomData,
+};
+
+use tracing::{info, warn, Level};
+
+/// Every output built from the text GARC `text_garc`, the other GARCs are decoded once in
+/// `rom` for every language
+fn dump_language(rom: &RomData, out_path: &Path, text_garc: &str, options: &Options) -> Result<()> {
+    let path = rom.path.as_path();
+    let text_garc_file = garc::open(&path.join(garc_files::BASE_PATH).join(text_garc))?;
+    let decoder = options.rom_type.text_decoder();
+    let text_files = garc::read_files_with::<text::TextFile>(&text_garc_file, |_| (decoder,))?;
+    if options.emit_names {
+        return dump_names(out_path, &text_files, options);
+    }
+    // Each selected output only opens the GARCs it's built from, the others may be missing
+    if !options.only.is_empty() {
+        let only = |name| options.only.iter().any(|output| output == name);
+        if only("pokedex") || only("learnsets") {
+            let names = if only("pokedex") {
+                let (dex_map, names) = pokemon::build_pokedex(rom, &text_files, options)?;
+                write_output(out_path, "pokedex", &dex_map, options)?;
+                names
+            } else {
+                pokemon::build_name_map(rom.pokemons()?, &text_files, options)
+            };
+            if only("learnsets") {
+                let learnset_map = learnset::build_learnsets(rom, &text_files, &names, options)?;
+                learnset::write_learnsets(out_path, &learnset_map, options)?;
+            }
+        }
+        if only("moves") {
+            moves::dump_moves(rom, out_path, &text_files, options)?;
+        }
+        if only("abilities") {
+            dump_abilities(path, out_path, &text_files, options)?;
+        }
+        if only("ability-ids") {
+            dump_ability_ids(out_path, &text_files, options)?;
+        }
+        return Ok(());
+    }
+    let up_to_date = |name| {
+        let up_to_date = is_up_to_date(path, out_path, name, options);
+        if up_to_date {
+            info!("{name} is up to date, skipping");
+        }
+        up_to_date
+    };
+    // The learnsets need the species names from the pokedex
+    let learnsets_up_to_date = up_to_date("learnsets");
+    if !up_to_date("pokedex")
+        || !learnsets_up_to_date
+        || options.species_full
+        || options.formats_data
+    {
+        let (dex_map, names) = pokemon::build_pokedex(rom, &text_files, options)?;
+        write_output(out_path, "pokedex", &dex_map, options)?;
+        if options.formats_data {
+            let formats_data = formats::build_formats_data(&dex_map, options);
+            write_output(out_path, "formats-data", &formats_data, options)?;
+        }
+        if !learnsets_up_to_date || options.species_full {
+            let learnset_map = learnset::build_learnsets(rom, &text_files, &names, options)?;
+            learnset::write_learnsets(out_path, &learnset_map, options)?;
+            if options.species_full {
+                let species = species_full(&dex_map, &learnset_map, options)?;
+                write_output(out_path, "species-full", &species, options)?;
+            }
+        }
+    }
+    if !up_to_date("moves") {
+        moves::dump_moves(rom, out_path, &text_files, options)?;
+    }
+    if !up_to_date("abilities") {
+        dump_abilities(path, out_path, &text_files, options)?;
+    }
+    Ok(())
 }
 
-fn to_id(s: String) -> String {
-    s.to_lowercase()
-        .chars()
-        .filter(|c| c.is_ascii_lowercase() || c.is_ascii_uppercase() || c.is_ascii_digit())
-        .collect()
+/// Prints `error` with its causes and exits with a failure status
+fn exit_with(error: Report) -> ! {
+    eprintln!("error: {error:#}");
+    process::exit(1)
 }
 
-#[allow(non_snake_case)]
-#[derive(Serialize)]
-struct AbilityJs {
-    name: String,
-    num: u32,
-    desc: String,
-    shortDesc: String,
+fn main() {
+    let options = Options::from_args(env::args().skip(1)).unwrap_or_else(|e| exit_with(e));
+    let level = match options.verbosity {
+        0 => Level::WARN,
+        1 => Level::INFO,
+        _ => Level::DEBUG,
+    };
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(std::io::stderr)
+        .init();
+    if let Err(e) = run(&options) {
+        exit_with(e);
+    }
 }
 
-fn dump_abilities(_rom_path: &Path, out_path: &Path, text_files: &[TextFile]) -> Result<()> {
-    let ability_names = &text_files[text_ids::ABILITY_NAMES].lines;
-    let ability_descs = &text_files[text_ids::ABILITY_DESCS].lines;
-
-    let ability_map: IndexMap<String, AbilityJs> = ability_names
-        .iter()
-        .enumerate()
-        .map(|(index, name)| {
-            (
-                to_id(name.clone()),
-                AbilityJs {
-                    name: name.clone(),
-                    num: index as _,
-                    desc: ability_descs[index].clone(),
-                    shortDesc: ability_descs[index].clone(),
-                },
-            )
-        })
-        .skip(1)
-        .collect();
-
-    let mut f = File::create(out_path.join("abilities.json"))?;
-    write!(f, "{}", serde_json::to_string_pretty(&ability_map)?)?;
-
+/// Runs the mode selected by `options`
+fn run(options: &Options) -> Result<()> {
+    let path = options.rom_path.as_path();
+    let out_path = options.out_path.as_path();
+
+    if options.count_only {
+        validate::print_garc_counts(path)?;
+        return Ok(());
+    }
+    if let Some((garc_path, out_dir)) = &options.extract_garc {
+        let count = garc::extract(&garc::open(garc_path)?, out_dir)?;
+        println!("extracted {count} subfiles to {}", out_dir.display());
+        return Ok(());
+    }
+    if let Some(typechart_path) = &options.check_typechart {
+        validate::check_typechart(typechart_path)?;
+        return Ok(());
+    }
+    if let Some((line, expected)) = &options.verify_keys {
+        let text_path = path.join(garc_files::BASE_PATH).join(garc_files::TEXT);
+        let text_garc = garc::open(&text_path)?;
+        let bytes = garc::file_bytes(&text_garc, text_ids::SPECIES_NAMES)?;
+        let decoders = text::find_keys(bytes, *line, expected)?;
+        for decoder in &decoders {
+            println!(
+                "key_base {:#06x} key_advance {:#06x}",
+                decoder.key_base, decoder.key_advance
+            );
+        }
+        println!(
+            "{} key sets decode line {line} to {expected:?}",
+            decoders.len()
+        );
+        return Ok(());
+    }
+    if options.dump_tms {
+        learnset::dump_tms(out_path, options)?;
+        return Ok(());
+    }
+    if options.dump_raw_stats {
+        dump_raw_stats(path, out_path, options)?;
+        return Ok(());
+    }
+
+    if options.validate_only {
+        let missing = validate::missing_learnset_moves(out_path, options)?;
+        for entry in &missing {
+            println!("learnset move not in moves: {entry}");
+        }
+        println!("{} learnset moves missing", missing.len());
+        for (kind, ids) in validate::check_moves(path, options)? {
+            println!("{kind}: {}", ids.len());
+            for id in ids {
+                println!("  {id}");
+            }
+        }
+        return Ok(());
+    }
+
+    if !options.force && !options.dry_run {
+        validate::check_out_dir(out_path)?;
+    }
+    let rom = RomData::new(path);
+    if options.languages.is_empty() {
+        dump_language(&rom, out_path, garc_files::TEXT, options)?;
+    } else {
+        for language in &options.languages {
+            let options = Options {
+                language: Some(*language),
+                ..options.clone()
+            };
+            dump_language(&rom, out_path, language.text_garc(), &options)
+                .wrap_err_with(|| format!("language {language}"))?;
+        }
+    }
+
+    let checks_learnsets = options.languages.is_empty()
+        && options.only.is_empty()
+        && !options.dry_run
+        && !options.emit_names
+        && !options.split_moves;
+    if checks_learnsets {
+        match validate::missing_learnset_moves(out_path, options) {
+            Ok(missing) => {
+                for entry in missing {
+                    warn!("learnset move not in moves: {entry}");
+                }
+            }
+            Err(e) => warn!("couldn't check learnset moves: {e}"),
+        }
+    }
+
+    if options.meta {
+        manifest::write_meta(out_path, options)?;
+    }
+    if options.manifest && !options.dry_run {
+        manifest::write_manifest(path, out_path, options)?;
+    }
+
+    if let Some(reference_path) = &options.check_ids_against {
+        validate::check_ids_against(out_path, reference_path)?;
+    }
     Ok(())
 }
-
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    let path = Path::new(&args[1]);
-    let out_path = Path::new(&args[2]);
-
-    let mut en_text_file = File::open(path.join("romfs/a/0/3/2")).unwrap();
-    let text_garc_file = GarcFile::read_le(&mut en_text_file).unwrap();
-    let text_files = garc::read_files::<text::TextFile>(&text_garc_file);
-    let names = pokemon::dump_pokes(path, out_path, &text_files).unwrap();
-    learnset::dump_learnsets(path, out_path, &text_files, &names).unwrap();
-    moves::dump_moves(path, out_path, &text_files).unwrap();
-    dump_abilities(path, out_path, &text_files).unwrap();
-}
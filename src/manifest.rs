@@ -0,0 +1,103 @@
+use std::{fs, path::Path};
+
+use color_eyre::Result;
+use indexmap::{IndexMap, IndexSet};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::{garc_files, is_output_file, language::Language, options::Options, write_output};
+
+#[derive(Serialize)]
+struct Manifest {
+    version: &'static str,
+    inputs: IndexMap<String, String>,
+    outputs: IndexMap<String, String>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize)]
+struct Meta {
+    gen: u8,
+    version: &'static str,
+    romType: &'static str,
+    languages: Vec<&'static str>,
+}
+
+fn sha256_file(path: &Path) -> Result<String> {
+    Ok(format!("{:x}", Sha256::digest(fs::read(path)?)))
+}
+
+/// The GARCs the outputs of this run are built from, with the text GARC of each language
+fn read_garcs(options: &Options) -> IndexSet<&'static str> {
+    let built = |name: &str| options.only.is_empty() || options.only.iter().any(|o| o == name);
+    let mut garc_paths: IndexSet<&str> = garc_files::OUTPUT_INPUTS
+        .iter()
+        .filter(|(name, _)| built(name))
+        .flat_map(|(_, inputs)| inputs.iter().copied())
+        .filter(|input| *input != garc_files::TEXT)
+        .collect();
+    if options.languages.is_empty() {
+        garc_paths.insert(garc_files::TEXT);
+    }
+    garc_paths.extend(options.languages.iter().copied().map(Language::text_garc));
+    garc_paths
+}
+
+/// Writes manifest.json with the SHA-256 of the input GARCs the run read and of the outputs
+/// already in `out_path`, to tell which ROM dump a committed output was built from
+pub fn write_manifest(rom_path: &Path, out_path: &Path, options: &Options) -> Result<()> {
+    let mut inputs = IndexMap::new();
+    for garc_path in read_garcs(options) {
+        let path = rom_path.join(garc_files::BASE_PATH).join(garc_path);
+        // A partial romfs only has the GARCs of the outputs it builds
+        if !path.exists() {
+            continue;
+        }
+        inputs.insert(garc_path.to_owned(), sha256_file(&path)?);
+    }
+
+    let mut outputs = IndexMap::new();
+    let mut entries: Vec<_> = fs::read_dir(out_path)?.collect::<Result<_, _>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        if !is_output_file(&file_name)
+            || file_name.starts_with("manifest.")
+            || !entry.file_type()?.is_file()
+        {
+            continue;
+        }
+        outputs.insert(file_name, sha256_file(&entry.path())?);
+    }
+
+    let manifest = Manifest {
+        version: env!("CARGO_PKG_VERSION"),
+        inputs,
+        outputs,
+    };
+    let options = Options {
+        language: None,
+        ..options.clone()
+    };
+    write_output(out_path, "manifest", &manifest, &options)
+}
+
+/// Writes meta.json with the generation, rom type and languages the outputs target, so
+/// consumers don't have to infer them from the bare maps
+pub fn write_meta(out_path: &Path, options: &Options) -> Result<()> {
+    let languages = match options.languages.as_slice() {
+        [] => vec![Language::English.code()],
+        languages => languages.iter().map(|language| language.code()).collect(),
+    };
+    let meta = Meta {
+        gen: options.rom_type.gen_digit(),
+        version: env!("CARGO_PKG_VERSION"),
+        romType: options.rom_type.code(),
+        languages,
+    };
+    let options = Options {
+        language: None,
+        ..options.clone()
+    };
+    write_output(out_path, "meta", &meta, &options)
+}
@@ -1,55 +1,104 @@
-use std::{collections::BTreeMap, default::Default, fs::File, io::Write, path::Path};
+use std::{collections::BTreeMap, default::Default, fs::File, io::BufReader, path::Path};
 
 use binrw::{BinRead, FilePtr};
-use color_eyre::Result;
+use color_eyre::{
+    eyre::{bail, ensure},
+    Result,
+};
 use indexmap::IndexMap;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
 
-use crate::{garc, garc_files, text::TextFile, text_ids, to_id};
+use crate::{
+    garc, garc_files,
+    options::Options,
+    summarize,
+    text::{single_line, TextFile},
+    to_id, write_output, RomData,
+};
 
 pub fn default<T: Default>() -> T {
     std::default::Default::default()
 }
 
+/// Move effect categories stored in `MoveStats::quality`, all listed though only a few
+/// are matched on
 #[allow(dead_code)]
+mod quality {
+    pub const DAMAGE: u8 = 0;
+    /// Inflicts a status without dealing damage
+    pub const STATUS: u8 = 1;
+    /// Non-damaging stat changes, on the user or the target
+    pub const STAT_CHANGE: u8 = 2;
+    pub const HEAL: u8 = 3;
+    pub const DAMAGE_STATUS: u8 = 4;
+    /// Swagger-like: confuses and raises the target's stats
+    pub const STATUS_STAT_CHANGE: u8 = 5;
+    pub const DAMAGE_TARGET_STATS: u8 = 6;
+    pub const DAMAGE_USER_STATS: u8 = 7;
+    pub const DAMAGE_DRAIN: u8 = 8;
+    pub const OHKO: u8 = 9;
+    pub const FIELD_EFFECT: u8 = 10;
+    pub const SIDE_EFFECT: u8 = 11;
+    pub const FORCE_SWITCH: u8 = 12;
+    pub const UNIQUE: u8 = 13;
+
+    pub const NAMES: &[&str] = &[
+        "damage",
+        "status",
+        "statChange",
+        "heal",
+        "damageStatus",
+        "statusStatChange",
+        "damageTargetStats",
+        "damageUserStats",
+        "damageDrain",
+        "ohko",
+        "fieldEffect",
+        "sideEffect",
+        "forceSwitch",
+        "unique",
+    ];
+}
+
 #[derive(BinRead, Debug)]
-struct MoveStats {
-    move_type: u8,
-    quality: u8,
-    category: u8,
-    power: u8,
+pub struct MoveStats {
+    pub move_type: u8,
+    pub quality: u8,
+    pub category: u8,
+    pub power: u8,
 
-    accuracy: u8,
-    pp: u8,
-    priority: i8,
-    hit_min_max: u8,
+    pub accuracy: u8,
+    pub pp: u8,
+    pub priority: i8,
+    pub hit_min_max: u8,
 
-    inflict: u16,
-    inflict_percent: u8,
-    inflict_duration: u8,
+    pub inflict: u16,
+    pub inflict_percent: u8,
+    pub inflict_duration: u8,
 
-    turn_min: u8,
-    turn_max: u8,
-    crit_stage: u8,
-    flinch: u8,
+    pub turn_min: u8,
+    pub turn_max: u8,
+    pub crit_stage: u8,
+    pub flinch: u8,
 
-    effect_sequence: u16,
-    recoil_absorption: i8,
-    heal: u8,
+    pub effect_sequence: u16,
+    pub recoil_absorption: i8,
+    pub heal: u8,
 
-    target: u8,
-    stat: [u8; 3],
-    stat_stage: [i8; 3],
-    stat_percent: [u8; 3],
+    pub target: u8,
+    pub stat: [u8; 3],
+    pub stat_stage: [i8; 3],
+    pub stat_percent: [u8; 3],
 
-    z_move: u16,
-    z_power: u8,
-    z_effect: u8,
+    pub z_move: u16,
+    pub z_power: u8,
+    pub z_effect: u8,
 
-    refresh_type: u8,
-    refresh_percent: u8,
+    pub refresh_type: u8,
+    pub refresh_percent: u8,
 
-    flags: u32,
+    pub flags: u32,
 }
 
 #[allow(dead_code)]
@@ -61,104 +110,171 @@ struct BinLinkedMoves {
     files: Vec<FilePtr<u32, MoveStats>>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 #[serde(untagged)]
-enum MoveJsAccuracy {
+pub enum MoveJsAccuracy {
     Number(i32),
     Boolean(bool),
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 #[serde(untagged)]
-enum MoveJsMultihit {
+pub enum MoveJsMultihit {
     Number(i32),
     Range(i32, i32),
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MoveJsSelfSwitch {
+    Boolean(bool),
+    Kind(String),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MoveJsIgnoreImmunity {
+    Boolean(bool),
+    /// Only ignores the immunity against these types
+    Types(BTreeMap<String, bool>),
+}
+
 #[serde_with::skip_serializing_none]
-#[derive(Serialize, Default, PartialEq)]
-struct BoostTable {
-    atk: Option<i8>,
-    def: Option<i8>,
-    spa: Option<i8>,
-    spd: Option<i8>,
-    spe: Option<i8>,
-    accuracy: Option<i8>,
-    evasion: Option<i8>,
+#[derive(Serialize, Deserialize, Default, PartialEq)]
+pub struct BoostTable {
+    pub atk: Option<i8>,
+    pub def: Option<i8>,
+    pub spa: Option<i8>,
+    pub spd: Option<i8>,
+    pub spe: Option<i8>,
+    pub accuracy: Option<i8>,
+    pub evasion: Option<i8>,
+}
+
+impl BoostTable {
+    pub fn get(&self, stat: &str) -> Option<i8> {
+        match stat {
+            "atk" => self.atk,
+            "def" => self.def,
+            "spa" => self.spa,
+            "spd" => self.spd,
+            "spe" => self.spe,
+            "accuracy" => self.accuracy,
+            "evasion" => self.evasion,
+            _ => None,
+        }
+    }
 }
 
-#[derive(Serialize)]
-struct SelfEffect {
-    boosts: BoostTable,
+#[derive(Serialize, Deserialize)]
+pub struct SelfEffect {
+    pub boosts: BoostTable,
 }
 
 #[allow(non_snake_case)]
 #[serde_with::skip_serializing_none]
-#[derive(Serialize, Default)]
-struct MoveSecondaryJs {
-    chance: i32,
-    boosts: Option<BoostTable>,
-    status: Option<String>,
-    volatileStatus: Option<String>,
+#[derive(Serialize, Deserialize, Default)]
+pub struct MoveSecondaryJs {
+    pub chance: i32,
+    pub boosts: Option<BoostTable>,
+    pub status: Option<String>,
+    pub volatileStatus: Option<String>,
     #[serde(rename = "self")]
-    selfEffects: Option<SelfEffect>,
+    pub selfEffects: Option<SelfEffect>,
 }
 
 #[allow(non_snake_case)]
 #[serde_with::skip_serializing_none]
-#[derive(Serialize, Default)]
-struct MoveJsZMove {
-    basePower: Option<i32>,
-    effect: Option<String>,
-    boosts: Option<BoostTable>,
+#[derive(Serialize, Deserialize, Default)]
+pub struct MoveJsZMove {
+    pub basePower: Option<i32>,
+    pub effect: Option<String>,
+    pub boosts: Option<BoostTable>,
 }
 
 #[allow(non_snake_case)]
 #[serde_with::skip_serializing_none]
-#[derive(Serialize)]
-struct MoveJs {
-    num: u32,
-    accuracy: MoveJsAccuracy,
-    basePower: u32,
-    category: String,
-    name: String,
-    pp: u32,
-    priority: i32,
-    critRatio: i32,
-    r#type: String,
-    target: String,
-    desc: String,
-    shortDesc: String,
-    flags: BTreeMap<String, u8>,
-
-    willCrit: Option<bool>,
-    drain: Option<(i32, i32)>,
-    recoil: Option<(i32, i32)>,
-    multihit: Option<MoveJsMultihit>,
+#[derive(Serialize, Deserialize)]
+pub struct MoveJs {
+    pub num: u32,
+    pub accuracy: MoveJsAccuracy,
+    pub basePower: u32,
+    /// Combines with another Pledge in doubles, `basePower` is only its power alone
+    pub isPledge: Option<bool>,
+    pub category: String,
+    pub name: String,
+    pub pp: u32,
+    pub priority: i32,
+    pub critRatio: i32,
+    pub r#type: String,
+    /// The type is decided in battle, `type` is only the ROM's base type
+    pub variableType: Option<bool>,
+    pub target: String,
+    pub nonGhostTarget: Option<String>,
+    pub desc: String,
+    pub shortDesc: String,
+    pub flags: BTreeMap<String, u8>,
+
+    pub willCrit: Option<bool>,
+    pub boosts: Option<BoostTable>,
+    pub drain: Option<(i32, i32)>,
+    pub recoil: Option<(i32, i32)>,
+    pub multihit: Option<MoveJsMultihit>,
+    pub selfSwitch: Option<MoveJsSelfSwitch>,
+    pub selfdestruct: Option<String>,
+    pub forceSwitch: Option<bool>,
+    pub status: Option<String>,
+    pub volatileStatus: Option<String>,
+    pub noPPBoosts: Option<bool>,
+    pub ignoreImmunity: Option<MoveJsIgnoreImmunity>,
+    pub thawsTarget: Option<bool>,
+    pub overrideOffensivePokemon: Option<String>,
+    pub overrideOffensiveStat: Option<String>,
+    pub overrideDefensiveStat: Option<String>,
     #[serde(rename = "self")]
-    selfEffects: Option<SelfEffect>,
-    zMove: Option<MoveJsZMove>,
-    secondaries: Option<Vec<MoveSecondaryJs>>,
+    pub selfEffects: Option<SelfEffect>,
+    pub zMove: Option<MoveJsZMove>,
+    /// Z-crystal turning a move into this Z-move
+    pub isZ: Option<String>,
+    /// Ids of the signature Z-moves this move turns into with a species' own crystal
+    pub signatureZMoves: Option<Vec<String>>,
+    /// Like Showdown, a lone secondary effect goes in `secondary` and several in
+    /// `secondaries`. Moves without one omit both rather than emit `secondary: null`.
+    pub secondary: Option<MoveSecondaryJs>,
+    pub secondaries: Option<Vec<MoveSecondaryJs>>,
+
+    // Raw decode info, only emitted with --debug
+    pub quality: Option<u8>,
+    pub qualityName: Option<String>,
+    pub unknownFlags: Option<u32>,
 }
 
-fn move_flags(mmove: &MoveStats) -> BTreeMap<String, u8> {
-    const FLAGS: &[(u32, &str)] = &[
-        (1 << 0, "contact"),
-        (1 << 1, "charge"),
-        (1 << 2, "recharge"),
-        (1 << 3, "protect"),
-        (1 << 4, "reflectable"),
-        (1 << 5, "snatch"),
-        (1 << 6, "mirror"),
-        (1 << 7, "punch"),
-        (1 << 8, "sound"),
-        (1 << 9, "gravity"),
-        (1 << 10, "defrost"),
-        (1 << 12, "heal"),
-        (1 << 13, "bypasssub"),
-        (1 << 16, "dance"),
-    ];
+/// Flag bits with a Showdown name. Bit 15 is set on moves that animate the ally in
+/// doubles and has no Showdown equivalent, it and any higher bit go to `unknownFlags`.
+const FLAGS: &[(u32, &str)] = &[
+    (1 << 0, "contact"),
+    (1 << 1, "charge"),
+    (1 << 2, "recharge"),
+    (1 << 3, "protect"),
+    (1 << 4, "reflectable"),
+    (1 << 5, "snatch"),
+    (1 << 6, "mirror"),
+    (1 << 7, "punch"),
+    (1 << 8, "sound"),
+    (1 << 9, "gravity"),
+    (1 << 10, "defrost"),
+    (1 << 11, "distance"),
+    (1 << 12, "heal"),
+    (1 << 13, "bypasssub"),
+    (1 << 14, "nonsky"),
+    (1 << 16, "dance"),
+];
 
+/// Only the flags stored in the ROM bitfield. Of the call/copy restrictions, which have no
+/// bit, `nosketch` and `nosleeptalk` come from lists in `manual_patches`. `failcopycat`,
+/// `failmimic`, `noassist`, `failencore`, `failinstruct`, `failmefirst` and `nometronome`
+/// aren't represented.
+fn move_flags(mmove: &MoveStats) -> BTreeMap<String, u8> {
     FLAGS
         .iter()
         .filter_map(|(bit, text)| {
@@ -171,6 +287,15 @@ fn move_flags(mmove: &MoveStats) -> BTreeMap<String, u8> {
         .collect()
 }
 
+/// Bits set in the ROM bitfield that `FLAGS` doesn't name
+fn unknown_flags(mmove: &MoveStats) -> Option<u32> {
+    let known = FLAGS.iter().fold(0, |known, (bit, _)| known | bit);
+    match mmove.flags & !known {
+        0 => None,
+        unknown => Some(unknown),
+    }
+}
+
 fn tuple_ratio(percent: i32) -> (i32, i32) {
     match percent {
         0 => (0, 1),
@@ -228,25 +353,96 @@ fn is_secondary_boost(_stat: u8, change: i8, chance: u8) -> bool {
     !(chance == 100 && change < 0)
 }
 
-const INFLICT: &[&str] = &["none", "par", "slp", "frz", "brn", "psn", "tox"];
+/// Non-volatile statuses by inflict id. There's no separate id for bad poison, Toxic and
+/// Poison Fang inflict 5 and are patched to `tox` in `manual_patches`.
+const INFLICT: &[&str] = &["none", "par", "slp", "frz", "brn", "psn"];
+
+/// Bind, Wrap and the other trapping moves
+const CAPTURE_EFFECT: u16 = 8;
+
+/// Showdown volatile statuses by inflict id, the ids after the statuses of `INFLICT`.
+/// 16 is unused.
+const VOLATILE: &[(u16, &str)] = &[
+    (6, "confusion"),
+    (7, "attract"),
+    (9, "nightmare"),
+    (10, "curse"),
+    (11, "taunt"),
+    (12, "torment"),
+    (13, "disable"),
+    (14, "yawn"),
+    (15, "healblock"),
+    (17, "foresight"),
+    (18, "leechseed"),
+    (19, "embargo"),
+    (20, "perishsong"),
+    (21, "ingrain"),
+];
+
+/// What the ROM `inflict` id of a move becomes. Status moves inflict it outright, like
+/// Will-O-Wisp's `status: "brn"`, while damaging moves get a chance to as a secondary.
+enum Inflict {
+    /// A status from `INFLICT`
+    Status(&'static str),
+    /// A volatile status from `VOLATILE`
+    Volatile(&'static str),
+    /// Part of the move's own effect rather than a chance, it goes on the move itself
+    Primary(&'static str),
+    /// An id missing from the tables, emitted as is
+    Unknown(u16),
+}
+
+fn inflict_kind(inflict: u16) -> Option<Inflict> {
+    let volatile = VOLATILE.iter().find(|(id, _)| *id == inflict);
+    match inflict {
+        0 => None,
+        n if (n as usize) < INFLICT.len() => Some(Inflict::Status(INFLICT[n as usize])),
+        CAPTURE_EFFECT => Some(Inflict::Primary("partiallytrapped")),
+        _ if volatile.is_some() => volatile.map(|(_, name)| Inflict::Volatile(name)),
+        n => Some(Inflict::Unknown(n)),
+    }
+}
+
+/// The status a status move inflicts, like Thunder Wave's `par`
+fn get_status(stats: &MoveStats) -> Option<String> {
+    match inflict_kind(stats.inflict)? {
+        Inflict::Status(status) if is_status_move(stats) => Some(status.to_owned()),
+        _ => None,
+    }
+}
+
+/// The volatile status a move always inflicts, like Bind's `partiallytrapped` or
+/// Confuse Ray's `confusion`
+fn get_volatile_status(stats: &MoveStats) -> Option<String> {
+    match inflict_kind(stats.inflict)? {
+        Inflict::Primary(volatile) => Some(volatile.to_owned()),
+        Inflict::Volatile(volatile) if is_status_move(stats) => Some(volatile.to_owned()),
+        Inflict::Unknown(id) if is_status_move(stats) => Some(id.to_string()),
+        _ => None,
+    }
+}
+
 fn get_secondaries(stats: &MoveStats) -> Option<Vec<MoveSecondaryJs>> {
     let mut effects = Vec::new();
-    // Capture effects are not secondary effects
-    const CAPTURE_EFFECT: u16 = 8;
-    if stats.inflict > 0 && stats.inflict != CAPTURE_EFFECT && stats.inflict_percent > 0 {
-        if (stats.inflict as usize) < INFLICT.len() {
-            effects.push(MoveSecondaryJs {
-                chance: stats.inflict_percent as _,
-                status: Some(INFLICT[stats.inflict as usize].to_owned()),
-                ..default()
-            })
-        } else {
-            effects.push(MoveSecondaryJs {
-                chance: stats.inflict_percent as _,
-                volatileStatus: Some(stats.inflict.to_string()),
-                ..default()
-            })
-        }
+    let chance = stats.inflict_percent as i32;
+    match inflict_kind(stats.inflict) {
+        _ if chance == 0 || is_status_move(stats) => {}
+        Some(Inflict::Status(status)) => effects.push(MoveSecondaryJs {
+            chance,
+            status: Some(status.to_owned()),
+            ..default()
+        }),
+        Some(Inflict::Volatile(volatile)) => effects.push(MoveSecondaryJs {
+            chance,
+            volatileStatus: Some(volatile.to_owned()),
+            ..default()
+        }),
+        Some(Inflict::Unknown(id)) => effects.push(MoveSecondaryJs {
+            chance,
+            volatileStatus: Some(id.to_string()),
+            ..default()
+        }),
+        Some(Inflict::Primary(_)) | None => {}
     }
 
     if stats.flinch > 0 {
@@ -258,8 +454,8 @@ fn get_secondaries(stats: &MoveStats) -> Option<Vec<MoveSecondaryJs>> {
     }
 
     for i in 0..3 {
-        if stats.stat[i] > 0 {
-            if stats.quality == 7 {
+        if stats.stat[i] > 0 && !is_status_move(stats) {
+            if stats.quality == quality::DAMAGE_USER_STATS {
                 if !is_secondary_boost(stats.stat[i], stats.stat_stage[i], stats.stat_percent[i]) {
                     continue;
                 }
@@ -287,11 +483,37 @@ fn get_secondaries(stats: &MoveStats) -> Option<Vec<MoveSecondaryJs>> {
     }
 }
 
+fn is_status_move(stats: &MoveStats) -> bool {
+    stats.category == 0
+}
+
+/// Status moves always apply their stat changes to their target, which is the user
+/// for self-targeting moves like Swords Dance. They're the move's primary effect, Growl
+/// gets `boosts: {atk: -1}` and no secondary, while a damaging move's guaranteed drop
+/// stays a 100% secondary like Showdown's Snarl.
+fn get_boosts(stats: &MoveStats) -> Option<BoostTable> {
+    if !is_status_move(stats) {
+        return None;
+    }
+    let mut table: BoostTable = default();
+    for i in 0..3 {
+        if stats.stat[i] > 0 {
+            boost_table_set(stats.stat[i], stats.stat_stage[i], &mut table)
+        }
+    }
+
+    if table == default() {
+        None
+    } else {
+        Some(table)
+    }
+}
+
 fn get_self_effect(stats: &MoveStats) -> Option<SelfEffect> {
     let mut table: BoostTable = default();
     for i in 0..3 {
         if stats.stat[i] > 0
-            && stats.quality == 7
+            && stats.quality == quality::DAMAGE_USER_STATS
             && !is_secondary_boost(stats.stat[i], stats.stat_stage[i], stats.stat_percent[i])
         {
             boost_table_set(stats.stat[i], stats.stat_stage[i], &mut table)
@@ -307,15 +529,59 @@ fn get_self_effect(stats: &MoveStats) -> Option<SelfEffect> {
 
 fn get_multihit(move_stats: &MoveStats) -> Option<MoveJsMultihit> {
     use MoveJsMultihit::*;
+    // Low nibble is the minimum hit count, high nibble the maximum:
+    // Double Kick is 0x22, Double Slap 0x52
     let min = move_stats.hit_min_max & 0xf;
     let max = move_stats.hit_min_max >> 4;
-    match (min, max) {
-        (0, 0) => None,
+    match (min.min(max), min.max(max)) {
+        (0, 0) | (1, 1) => None,
+        (0, n) => Some(Number(n as i32)),
         (a, b) if a == b => Some(Number(a as i32)),
         (a, b) => Some(Range(a as i32, b as i32)),
     }
 }
 
+/// Maps the ROM target to Showdown's, in doubles 4 (Earthquake) also hits the ally
+/// while 5 (Heat Wave, Dazzling Gleam) only hits foes
+fn move_target(target: u8) -> &'static str {
+    match target {
+        1 => "adjacentAllyOrSelf", // Acupressure
+        2 => "adjacentAlly",       // Helping Hand
+        3 => "adjacentFoe",
+        4 => "allAdjacent",
+        5 => "allAdjacentFoes",
+        6 => "allies",
+        7 => "self",
+        8 | 10 => "all",
+        9 => "randomNormal",
+        11 => "foeSide",
+        12 => "allySide",
+        13 => "scripted", // Counter, Mirror Coat
+        _ => "normal",
+    }
+}
+
+/// Whether `move_target` knows the ROM target, the others are written as normal
+pub fn is_known_target(target: u8) -> bool {
+    target <= 13
+}
+
+/// Generation a move was introduced in, moves are numbered in release order
+pub fn move_gen(num: u16) -> Option<u8> {
+    match num {
+        0 => None,
+        1..=165 => Some(1),
+        166..=251 => Some(2),
+        252..=354 => Some(3),
+        355..=467 => Some(4),
+        468..=559 => Some(5),
+        560..=621 => Some(6),
+        _ => Some(7),
+    }
+}
+
+/// The ROM value is kept as is, it follows Showdown's table of Z-move powers by base power
+/// but moves with a special power (multi-hit, OHKO, variable power) legitimately differ
 fn get_z_move(move_stats: &MoveStats) -> Option<MoveJsZMove> {
     match move_stats.z_power {
         0 => None,
@@ -326,6 +592,28 @@ fn get_z_move(move_stats: &MoveStats) -> Option<MoveJsZMove> {
     }
 }
 
+/// Type Z-crystals by type index, the crystal of a type's generic Z-moves
+const TYPE_Z_CRYSTALS: [&str; 18] = [
+    "normaliumz",
+    "fightiniumz",
+    "flyiniumz",
+    "poisoniumz",
+    "groundiumz",
+    "rockiumz",
+    "buginiumz",
+    "ghostiumz",
+    "steeliumz",
+    "firiumz",
+    "wateriumz",
+    "grassiumz",
+    "electriumz",
+    "psychiumz",
+    "iciumz",
+    "dragoniumz",
+    "darkiniumz",
+    "fairiumz",
+];
+
 const BULLET_MOVES: &[&str] = &[
     "triplecannonade",
     "bugbomber",
@@ -370,6 +658,178 @@ const BITE_MOVES: &[&str] = &[
     "thunderfang",
   ];
 
+// Powder and wind aren't part of the ROM flag bitfield, they're listed by hand like bullet/pulse
+const POWDER_MOVES: &[&str] = &[
+    "cottonspore",
+    "poisonpowder",
+    "powder",
+    "ragepowder",
+    "sleeppowder",
+    "spore",
+    "stunspore",
+];
+
+// Switching out is handled by the move's unique effect, which isn't decoded. The kind of
+// switch is given for the ones that aren't plain `selfSwitch: true`.
+const SELF_SWITCH_MOVES: &[(&str, Option<&str>)] = &[
+    ("uturn", None),
+    ("voltswitch", None),
+    ("partingshot", None),
+    ("batonpass", Some("copyvolatile")),
+];
+
+const NO_SKETCH_MOVES: &[&str] = &["chatter", "struggle"];
+
+// Moves Sleep Talk can't call, the two-turn charge moves included
+const NO_SLEEP_TALK_MOVES: &[&str] = &[
+    "assist",
+    "beakblast",
+    "belch",
+    "bide",
+    "bounce",
+    "celebrate",
+    "chatter",
+    "copycat",
+    "dig",
+    "dive",
+    "fly",
+    "focuspunch",
+    "freezeshock",
+    "geomancy",
+    "holdhands",
+    "iceburn",
+    "mefirst",
+    "metronome",
+    "mimic",
+    "mirrormove",
+    "naturepower",
+    "phantomforce",
+    "razorwind",
+    "shadowforce",
+    "shelltrap",
+    "sketch",
+    "skullbash",
+    "skyattack",
+    "sleeptalk",
+    "solarbeam",
+    "solarblade",
+    "struggle",
+    "uproar",
+];
+
+// Moves fainting the user, "always" even when they miss, "ifHit" only when they land
+const SELF_DESTRUCT_MOVES: &[(&str, &str)] = &[
+    ("explosion", "always"),
+    ("selfdestruct", "always"),
+    ("mistyexplosion", "always"),
+    ("finalgambit", "ifHit"),
+    ("healingwish", "ifHit"),
+    ("lunardance", "ifHit"),
+    ("memento", "ifHit"),
+];
+
+// Damaging moves that also phaze the target
+const FORCE_SWITCH_MOVES: &[&str] = &["dragontail", "circlethrow"];
+
+/// A const stand-in for `MoveJsIgnoreImmunity`
+enum IgnoreImmunity {
+    /// Whether the move hits through every type immunity
+    All(bool),
+    /// The type whose immunity the move hits through
+    Type(&'static str),
+}
+
+// Moves that hit through type immunities, or a status move that explicitly doesn't.
+// This is handled by the effect code, the ROM data has nothing for it.
+const IGNORE_IMMUNITY_MOVES: &[(&str, IgnoreImmunity)] = &[
+    ("bide", IgnoreImmunity::All(true)),
+    ("futuresight", IgnoreImmunity::All(true)),
+    ("doomdesire", IgnoreImmunity::All(true)),
+    ("thousandarrows", IgnoreImmunity::Type("Ground")),
+    // Status moves skip immunities, Thunder Wave still fails on Ground types
+    ("thunderwave", IgnoreImmunity::All(false)),
+];
+
+// Non-Fire moves that thaw a frozen target. Fire-type damaging moves (Flamethrower, ...)
+// thaw it too, but Showdown's freeze condition checks their type and its data never sets
+// the field on them, so they're left out. The ROM's defrost flag is the user thawing itself.
+const THAWS_TARGET_MOVES: &[&str] = &["scald", "steameruption", "scorchingsands"];
+
+// Damage calculation overrides, part of the effect code like the ones above. Taken from
+// the moves setting `overrideOffensivePokemon`, `overrideOffensiveStat` and
+// `overrideDefensiveStat` in Showdown's moves.ts. Like every table here, later-gen moves
+// are kept since the ROM can have them (its TMs include Gen 8 moves), and ids the ROM
+// lacks are skipped.
+const OFFENSIVE_POKEMON_OVERRIDES: &[(&str, &str)] = &[("foulplay", "target")];
+const OFFENSIVE_STAT_OVERRIDES: &[(&str, &str)] = &[("bodypress", "def")];
+const DEFENSIVE_STAT_OVERRIDES: &[(&str, &str)] = &[
+    ("psyshock", "def"),
+    ("psystrike", "def"),
+    ("secretsword", "def"),
+];
+
+// Base move, signature Z-move and its crystal. The ROM's z_move only points to the generic
+// Z-move of the type, signature ones come from the item data.
+const SIGNATURE_Z_MOVES: &[(&str, &str, &str)] = &[
+    ("volttackle", "catastropika", "pikaniumz"),
+    ("thunderbolt", "10000000voltthunderbolt", "pikashuniumz"),
+    ("thunderbolt", "stokedsparksurfer", "aloraichiumz"),
+    ("spiritshackle", "sinisterarrowraid", "decidiumz"),
+    ("darkestlariat", "maliciousmoonsault", "inciniumz"),
+    ("sparklingaria", "oceanicoperetta", "primariumz"),
+    ("naturesmadness", "guardianofalola", "tapuniumz"),
+    ("psychic", "genesissupernova", "mewniumz"),
+    ("spectralthief", "soulstealing7starstrike", "marshadiumz"),
+    ("lastresort", "extremeevoboost", "eeviumz"),
+    ("gigaimpact", "pulverizingpancake", "snorliumz"),
+    ("stoneedge", "splinteredstormshards", "lycaniumz"),
+    ("playrough", "letssnuggleforever", "mimikiumz"),
+    ("clangingscales", "clangoroussoulblaze", "kommoniumz"),
+    ("sunsteelstrike", "searingsunrazesmash", "solganiumz"),
+    ("moongeistbeam", "menacingmoonrazemaelstrom", "lunaliumz"),
+    ("photongeyser", "lightthatburnsthesky", "ultranecroziumz"),
+];
+
+// Moves targeting differently when used by a non-Ghost, with the Ghost target and the
+// non-Ghost one. The ROM has a single target for them.
+const NON_GHOST_TARGETS: &[(&str, &str, &str)] = &[("curse", "randomNormal", "self")];
+
+// Moves whose type comes from the weather, an item or the user, Showdown sets it in a
+// callback and the ROM only has the base type
+const VARIABLE_TYPE_MOVES: &[&str] = &[
+    "weatherball",
+    "judgment",
+    "multiattack",
+    "technoblast",
+    "naturalgift",
+    "hiddenpower",
+    "revelationdance",
+];
+
+// Moves combining into a stronger one when two are used in the same turn, Showdown does it
+// in a callback
+const PLEDGE_MOVES: &[&str] = &["grasspledge", "firepledge", "waterpledge"];
+
+// Moves badly poisoning instead of poisoning, the ROM gives them the plain poison id
+const BADLY_POISON_MOVES: &[&str] = &["toxic", "poisonfang"];
+
+// PP Ups can't be used on these, the ROM has no bit for it
+const NO_PP_BOOSTS_MOVES: &[&str] = &["sketch", "struggle"];
+
+const WIND_MOVES: &[&str] = &[
+    "aircutter",
+    "blizzard",
+    "fairywind",
+    "gust",
+    "heatwave",
+    "hurricane",
+    "icywind",
+    "petalblizzard",
+    "sandstorm",
+    "tailwind",
+    "twister",
+    "whirlwind",
+];
 
 fn manual_patches(mut moves: IndexMap<String, MoveJs>) -> IndexMap<String, MoveJs> {
     for mv in BULLET_MOVES {
@@ -384,24 +844,162 @@ fn manual_patches(mut moves: IndexMap<String, MoveJs>) -> IndexMap<String, MoveJ
         let Some(mv_js) = moves.get_mut(*mv) else { continue; };
         mv_js.flags.insert("bite".to_owned(), 1);
     }
+    for mv in POWDER_MOVES {
+        let Some(mv_js) = moves.get_mut(*mv) else { continue; };
+        mv_js.flags.insert("powder".to_owned(), 1);
+    }
+    for mv in WIND_MOVES {
+        let Some(mv_js) = moves.get_mut(*mv) else { continue; };
+        mv_js.flags.insert("wind".to_owned(), 1);
+    }
+    for mv in NO_SKETCH_MOVES {
+        let Some(mv_js) = moves.get_mut(*mv) else { continue; };
+        mv_js.flags.insert("nosketch".to_owned(), 1);
+    }
+    for mv in NO_SLEEP_TALK_MOVES {
+        let Some(mv_js) = moves.get_mut(*mv) else { continue; };
+        mv_js.flags.insert("nosleeptalk".to_owned(), 1);
+    }
+    for mv in FORCE_SWITCH_MOVES {
+        let Some(mv_js) = moves.get_mut(*mv) else { continue; };
+        mv_js.forceSwitch = Some(true);
+    }
+    for mv in NO_PP_BOOSTS_MOVES {
+        let Some(mv_js) = moves.get_mut(*mv) else { continue; };
+        mv_js.noPPBoosts = Some(true);
+    }
+    for mv in THAWS_TARGET_MOVES {
+        let Some(mv_js) = moves.get_mut(*mv) else { continue; };
+        mv_js.thawsTarget = Some(true);
+    }
+    for (mv, pokemon) in OFFENSIVE_POKEMON_OVERRIDES {
+        let Some(mv_js) = moves.get_mut(*mv) else { continue; };
+        mv_js.overrideOffensivePokemon = Some((*pokemon).to_owned());
+    }
+    for (mv, stat) in OFFENSIVE_STAT_OVERRIDES {
+        let Some(mv_js) = moves.get_mut(*mv) else { continue; };
+        mv_js.overrideOffensiveStat = Some((*stat).to_owned());
+    }
+    for (mv, stat) in DEFENSIVE_STAT_OVERRIDES {
+        let Some(mv_js) = moves.get_mut(*mv) else { continue; };
+        mv_js.overrideDefensiveStat = Some((*stat).to_owned());
+    }
+    for (mv, immune_type) in IGNORE_IMMUNITY_MOVES {
+        let Some(mv_js) = moves.get_mut(*mv) else { continue; };
+        mv_js.ignoreImmunity = Some(match immune_type {
+            IgnoreImmunity::All(ignores) => MoveJsIgnoreImmunity::Boolean(*ignores),
+            IgnoreImmunity::Type(immune_type) => {
+                MoveJsIgnoreImmunity::Types([((*immune_type).to_owned(), true)].into())
+            }
+        });
+    }
+    for (mv, z_move, crystal) in SIGNATURE_Z_MOVES {
+        let Some(z_move_js) = moves.get_mut(*z_move) else { continue; };
+        z_move_js.isZ = Some((*crystal).to_owned());
+        let Some(mv_js) = moves.get_mut(*mv) else { continue; };
+        mv_js
+            .signatureZMoves
+            .get_or_insert_with(Vec::new)
+            .push((*z_move).to_owned());
+    }
+    for mv in VARIABLE_TYPE_MOVES {
+        let Some(mv_js) = moves.get_mut(*mv) else { continue; };
+        mv_js.variableType = Some(true);
+    }
+    for mv in PLEDGE_MOVES {
+        let Some(mv_js) = moves.get_mut(*mv) else { continue; };
+        mv_js.isPledge = Some(true);
+    }
+    for (mv, target, non_ghost_target) in NON_GHOST_TARGETS {
+        let Some(mv_js) = moves.get_mut(*mv) else { continue; };
+        mv_js.target = (*target).to_owned();
+        mv_js.nonGhostTarget = Some((*non_ghost_target).to_owned());
+    }
+    for mv in BADLY_POISON_MOVES {
+        let Some(mv_js) = moves.get_mut(*mv) else { continue; };
+        if mv_js.status.as_deref() == Some("psn") {
+            mv_js.status = Some("tox".to_owned());
+        }
+        let secondaries = mv_js
+            .secondary
+            .iter_mut()
+            .chain(mv_js.secondaries.iter_mut().flatten());
+        for secondary in secondaries.filter(|s| s.status.as_deref() == Some("psn")) {
+            secondary.status = Some("tox".to_owned());
+        }
+    }
+    for (mv, kind) in SELF_DESTRUCT_MOVES {
+        let Some(mv_js) = moves.get_mut(*mv) else { continue; };
+        mv_js.selfdestruct = Some((*kind).to_owned());
+    }
+    for (mv, kind) in SELF_SWITCH_MOVES {
+        let Some(mv_js) = moves.get_mut(*mv) else { continue; };
+        mv_js.selfSwitch = Some(match kind {
+            None => MoveJsSelfSwitch::Boolean(true),
+            Some(kind) => MoveJsSelfSwitch::Kind((*kind).to_owned()),
+        });
+    }
     moves
 }
 
-pub fn dump_moves(rom_path: &Path, out_path: &Path, text_files: &[TextFile]) -> Result<()> {
-    let move_names = &text_files[text_ids::MOVE_NAMES].lines;
-    let move_descs = &text_files[text_ids::MOVE_DESCS].lines;
-    let type_names = &text_files[text_ids::TYPE_NAMES].lines;
+pub fn dump_moves(
+    rom: &RomData,
+    out_path: &Path,
+    text_files: &[TextFile],
+    options: &Options,
+) -> Result<()> {
+    let move_map = build_moves(rom, text_files, options)?;
+    if options.split_moves {
+        for category in ["Physical", "Special", "Status"] {
+            let category_map: IndexMap<&String, &MoveJs> = move_map
+                .iter()
+                .filter(|(_, mv)| mv.category == category)
+                .collect();
+            let name = format!("moves-{}", category.to_lowercase());
+            write_output(out_path, &name, &category_map, options)?;
+        }
+        return Ok(());
+    }
+    write_output(out_path, "moves", &move_map, options)
+}
 
-    let move_path = rom_path.join(garc_files::BASE_PATH).join(garc_files::MOVE);
-    let moves =
-        &garc::read_files::<BinLinkedMoves>(&garc::GarcFile::read_le(&mut File::open(move_path)?)?)
-            [0]
-        .files;
-    let move_map: IndexMap<String, MoveJs> = moves
+/// The moves by id, with the manual patches applied
+pub fn build_moves(
+    rom: &RomData,
+    text_files: &[TextFile],
+    options: &Options,
+) -> Result<IndexMap<String, MoveJs>> {
+    let ids = options.text_ids();
+    let move_names = &text_files[ids.move_names].lines;
+    let move_descs: Vec<String> = text_files[ids.move_descs]
+        .lines
+        .iter()
+        .map(|line| single_line(line))
+        .collect();
+    let type_names = &text_files[ids.type_names].lines;
+
+    let moves = rom.moves()?;
+    ensure!(
+        move_descs.len() >= moves.len(),
+        "text file {} has {} lines for {} moves, the move_descs text id is likely wrong",
+        ids.move_descs,
+        move_descs.len(),
+        moves.len()
+    );
+    let mut move_map: IndexMap<String, MoveJs> = moves
         .iter()
         .enumerate()
         .map(|(index, cmove)| {
             let name = &move_names[index];
+            let (secondary, secondaries) = match get_secondaries(cmove) {
+                Some(mut effects) if effects.len() == 1 => (effects.pop(), None),
+                effects => (None, effects),
+            };
+            let category = match cmove.category {
+                1 => "Physical",
+                2 => "Special",
+                _ => "Status",
+            };
             (
                 to_id(name.clone()),
                 MoveJs {
@@ -411,15 +1009,16 @@ pub fn dump_moves(rom_path: &Path, out_path: &Path, text_files: &[TextFile]) ->
                         101 => MoveJsAccuracy::Boolean(true),
                         a => MoveJsAccuracy::Number(a as _),
                     },
-                    basePower: cmove.power as _,
+                    // Some status moves carry a stray power byte, Showdown expects 0
+                    basePower: match category {
+                        "Status" => 0,
+                        _ => cmove.power as _,
+                    },
+                    isPledge: None,
                     pp: cmove.pp as _,
-                    category: match cmove.category {
-                        1 => "Physical",
-                        2 => "Special",
-                        _ => "Status",
-                    }
-                    .to_owned(),
-                    priority: cmove.priority as _,
+                    category: category.to_owned(),
+                    // Sign-extended, Trick Room is stored as 0xF9
+                    priority: i32::from(cmove.priority),
                     critRatio: (cmove.crit_stage as i32) + 1,
                     willCrit: match cmove.crit_stage {
                         6 => Some(true),
@@ -428,30 +1027,89 @@ pub fn dump_moves(rom_path: &Path, out_path: &Path, text_files: &[TextFile]) ->
                     flags: move_flags(cmove),
                     drain: get_drain(cmove),
                     recoil: get_recoil(cmove),
-                    secondaries: get_secondaries(cmove),
+                    boosts: get_boosts(cmove),
+                    secondary,
+                    secondaries,
                     selfEffects: get_self_effect(cmove),
                     r#type: type_names[cmove.move_type as usize].clone(),
-                    target: match cmove.target {
-                        1 | 2 => "adjacentAlly",
-                        4 => "allAdjacent",
-                        5 => "allAdjacentFoes",
-                        7 => "self",
-                        9 => "randomNormal",
-                        _ => "normal",
-                    }
-                    .to_owned(),
+                    variableType: None,
+                    target: move_target(cmove.target).to_owned(),
+                    nonGhostTarget: None,
                     multihit: get_multihit(cmove),
+                    selfSwitch: None,
+                    selfdestruct: None,
+                    noPPBoosts: None,
+                    ignoreImmunity: None,
+                    thawsTarget: None,
+                    overrideOffensivePokemon: None,
+                    overrideOffensiveStat: None,
+                    overrideDefensiveStat: None,
+                    status: get_status(cmove),
+                    volatileStatus: get_volatile_status(cmove),
+                    // Only covers the status phazing moves
+                    forceSwitch: (cmove.quality == quality::FORCE_SWITCH).then_some(true),
                     zMove: get_z_move(cmove),
+                    isZ: None,
+                    signatureZMoves: None,
                     desc: move_descs[index].clone(),
-                    shortDesc: move_descs[index].clone(),
+                    shortDesc: summarize(&move_descs[index]),
+                    quality: options.debug.then_some(cmove.quality),
+                    qualityName: options
+                        .debug
+                        .then(|| quality::NAMES.get(cmove.quality as usize))
+                        .flatten()
+                        .map(|name| (*name).to_owned()),
+                    unknownFlags: options.debug.then(|| unknown_flags(cmove)).flatten(),
                 },
             )
         })
-        .skip(1)
+        .skip(options.skipped_slots())
         .collect();
 
-    let move_map = manual_patches(move_map);
-    let mut f = File::create(out_path.join("moves.json"))?;
-    write!(f, "{}", serde_json::to_string_pretty(&move_map)?)?;
-    Ok(())
+    for (index, cmove) in moves.iter().enumerate() {
+        if let Some(Inflict::Unknown(id)) = inflict_kind(cmove.inflict) {
+            warn!("{}: inflict id {id} missing from VOLATILE", move_names[index]);
+        }
+        if !is_known_target(cmove.target) {
+            warn!(
+                "{}: unknown target {}, written as normal",
+                move_names[index], cmove.target
+            );
+        }
+    }
+
+    // Every Z-move some move converts into is a generic one, made with its type's crystal
+    for cmove in moves.iter().filter(|cmove| cmove.z_move != 0) {
+        let z_index = cmove.z_move as usize;
+        let Some(z_move) = moves.get(z_index) else {
+            continue;
+        };
+        let id = to_id(move_names[z_index].clone());
+        if let Some(z_move_js) = move_map.get_mut(&id) {
+            z_move_js.isZ = TYPE_Z_CRYSTALS
+                .get(z_move.move_type as usize)
+                .map(|crystal| (*crystal).to_owned());
+        }
+    }
+
+    info!("applying move manual patches");
+    Ok(manual_patches(move_map))
+}
+
+/// Decodes every move of the MOVE GARC, indexed by move number
+pub fn read_move_stats(rom_path: &Path) -> Result<Vec<MoveStats>> {
+    let move_path = rom_path.join(garc_files::BASE_PATH).join(garc_files::MOVE);
+    let move_garc = garc::open(&move_path)?;
+    let Some(container) = garc::read_files::<BinLinkedMoves>(&move_garc)?.pop() else {
+        bail!("{} has no move container", move_path.display());
+    };
+    Ok(container
+        .files
+        .into_iter()
+        .map(FilePtr::into_inner)
+        .collect())
+}
+
+pub fn load_moves(path: &Path) -> Result<IndexMap<String, MoveJs>> {
+    Ok(serde_json::from_reader(BufReader::new(File::open(path)?))?)
 }
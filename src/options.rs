@@ -0,0 +1,378 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    language::{Language, TextIds},
+    text::TextDecoder,
+};
+
+use color_eyre::{
+    eyre::{bail, eyre},
+    Result,
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    #[default]
+    Json,
+    /// Showdown `data/*.ts` files with their `import type` header
+    ShowdownTs,
+    /// MessagePack with named fields, for consumers that can't afford parsing JSON
+    #[cfg(feature = "msgpack")]
+    MsgPack,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum RomType {
+    SunMoon,
+    #[default]
+    UltraSunMoon,
+}
+
+impl RomType {
+    /// Generation digit used in Showdown learnset sources, e.g. "7L5"
+    pub fn gen_digit(&self) -> u8 {
+        match self {
+            RomType::SunMoon | RomType::UltraSunMoon => 7,
+        }
+    }
+
+    /// Code used in `--rom-type`
+    pub fn code(&self) -> &'static str {
+        match self {
+            RomType::SunMoon => "sm",
+            RomType::UltraSunMoon => "usum",
+        }
+    }
+
+    /// Text encryption keys, a game with other keys gets its own decoder here
+    pub fn text_decoder(&self) -> TextDecoder {
+        match self {
+            RomType::SunMoon | RomType::UltraSunMoon => TextDecoder::GEN7,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum LearnsetFormat {
+    /// A list of `{move, how, level}` entries per species
+    #[default]
+    List,
+    /// Showdown's `{learnset: {move: ["7L5", "7M"]}}`
+    Showdown,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    pub rom_path: PathBuf,
+    pub out_path: PathBuf,
+
+    pub rom_type: RomType,
+    pub format: OutputFormat,
+    pub learnset_format: LearnsetFormat,
+    /// Emit raw decode fields (move quality, ...) in the output
+    pub debug: bool,
+    /// Print the formes missing from FORME_NAMES while generating the pokedex
+    pub list_unmapped_formes: bool,
+    /// Only print the file count of each GARC
+    pub count_only: bool,
+    /// Only write tms.json
+    pub dump_tms: bool,
+    /// Only write raw_stats.csv, the personal data with numeric ids
+    pub dump_raw_stats: bool,
+    /// Only check the outputs already in the output directory
+    pub validate_only: bool,
+    /// Only write the raw subfiles of a GARC to a directory
+    pub extract_garc: Option<(PathBuf, PathBuf)>,
+    /// Only check the matchups of a Showdown typechart.json
+    pub check_typechart: Option<PathBuf>,
+    /// Only search the text keys decoding this species names line to this name
+    pub verify_keys: Option<(usize, String)>,
+    /// Showdown data directory to compare the generated ids against
+    pub check_ids_against: Option<PathBuf>,
+    /// Languages dumped in one run, each output gets the language code in its file name
+    pub languages: Vec<Language>,
+    /// Language of the current dump when several are requested
+    pub language: Option<Language>,
+    /// Write manifest.json with the hashes of the inputs and outputs
+    pub manifest: bool,
+    /// Write meta.json with the generation, version, rom type and languages
+    pub meta: bool,
+    /// JSON indentation, two spaces when unset
+    pub indent: Option<String>,
+    /// Write to an output directory holding files the tool didn't write
+    pub force: bool,
+    /// Regenerate outputs even when they're newer than their input GARCs and were written
+    /// with the same options
+    pub rebuild: bool,
+    /// Only build these outputs, from `ONLY_OUTPUTS`
+    pub only: Vec<String>,
+    /// Emit every forme as a standalone entry without `baseSpecies`/`formes` links, with
+    /// its own learnset
+    pub flatten_formes: bool,
+    /// Fold formes with the stats, types and abilities of their base species into its
+    /// `cosmeticFormes`
+    pub compact_formes: bool,
+    /// Also give Showdown learnset sources in every generation from this one where the
+    /// move existed
+    pub learnset_sources_from: Option<u8>,
+    /// Also write species-full.json, each pokedex entry with its learnset
+    pub species_full: bool,
+    /// Also write formats-data.json, a starting tier for each pokedex entry
+    pub formats_data: bool,
+    /// Tier of the species no rule classifies, `formats::DEFAULT_TIER` when unset
+    pub default_tier: Option<String>,
+    /// Build every output but only print the size it would have
+    pub dry_run: bool,
+    /// Only write names.json
+    pub emit_names: bool,
+    /// Write moves-physical/special/status.json instead of moves.json
+    pub split_moves: bool,
+    /// 0 only logs warnings and errors, 1 (`-v`) each phase, 2 (`-vv`) each skipped entry
+    pub verbosity: u8,
+    /// Keep slot 0 (the Egg, the empty move and ability) in the outputs
+    pub no_skip_egg: bool,
+    /// Give abilities the `flags` map of modern Showdown
+    pub ability_flags: bool,
+}
+
+/// Outputs that can be selected with `--only`
+pub const ONLY_OUTPUTS: &[&str] = &["pokedex", "learnsets", "moves", "abilities", "ability-ids"];
+
+fn next_value(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<String> {
+    args.next().ok_or_else(|| eyre!("{flag} expects a value"))
+}
+
+/// Flags and positionals from a config file. Every key is a flag without its `--`, `rom` and
+/// `out` are the positionals, lists are joined by commas and `false` leaves a switch off.
+fn config_args(path: &Path) -> Result<(Vec<String>, Vec<String>)> {
+    let table: toml::Table = fs::read_to_string(path)?
+        .parse()
+        .map_err(|e| eyre!("{}: {e}", path.display()))?;
+    let mut flags = Vec::new();
+    let mut positional = vec![None, None];
+    for (key, value) in table {
+        let value = match value {
+            toml::Value::Boolean(false) => continue,
+            toml::Value::Boolean(true) => None,
+            toml::Value::String(s) => Some(s),
+            toml::Value::Integer(n) => Some(n.to_string()),
+            toml::Value::Array(values) => {
+                let values: Option<Vec<&str>> = values.iter().map(|v| v.as_str()).collect();
+                let Some(values) = values else {
+                    bail!("{}: {key} expects a list of strings", path.display());
+                };
+                Some(values.join(","))
+            }
+            value => bail!("{}: unsupported value {value} for {key}", path.display()),
+        };
+        match (key.as_str(), value) {
+            ("rom", Some(value)) => positional[0] = Some(value),
+            ("out", Some(value)) => positional[1] = Some(value),
+            (_, value) => {
+                flags.push(format!("--{key}"));
+                flags.extend(value);
+            }
+        }
+    }
+    Ok((flags, positional.into_iter().flatten().collect()))
+}
+
+impl Options {
+    /// Parses the command line on top of the config file given with `--config`, or
+    /// `config.toml` next to the binary. Flags and positionals given on the command line
+    /// replace the ones of the file.
+    pub fn from_args(args: impl Iterator<Item = String>) -> Result<Self> {
+        let default_config = env::current_exe().map(|exe| exe.with_file_name("config.toml"));
+        Self::from_args_with_config(args, default_config.ok())
+    }
+
+    /// [`Options::from_args`] with `default_config` read, if it exists, when no `--config`
+    /// is given
+    pub fn from_args_with_config(
+        args: impl Iterator<Item = String>,
+        default_config: Option<PathBuf>,
+    ) -> Result<Self> {
+        let mut args: Vec<String> = args.collect();
+        let config_path = match args.iter().position(|arg| arg == "--config") {
+            Some(i) => {
+                let Some(path) = args.get(i + 1).map(PathBuf::from) else {
+                    bail!("--config expects a value");
+                };
+                args.drain(i..i + 2);
+                Some(path)
+            }
+            None => default_config.filter(|path| path.exists()),
+        };
+        let (mut config_flags, config_positional) = match config_path {
+            Some(path) => config_args(&path)?,
+            None => Default::default(),
+        };
+        // Drop the file's flags given again on the command line, with their value
+        let mut i = 0;
+        while i < config_flags.len() {
+            let len = 1 + config_flags[i + 1..]
+                .iter()
+                .take_while(|arg| !arg.starts_with("--"))
+                .count();
+            if args.contains(&config_flags[i]) {
+                config_flags.drain(i..i + len);
+            } else {
+                i += len;
+            }
+        }
+        Self::parse(config_flags.into_iter().chain(args), config_positional)
+    }
+
+    fn parse(
+        mut args: impl Iterator<Item = String>,
+        config_positional: Vec<String>,
+    ) -> Result<Self> {
+        let mut options = Options::default();
+        let mut positional = Vec::new();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--debug" => options.debug = true,
+                "-v" => options.verbosity += 1,
+                "-vv" => options.verbosity += 2,
+                "--count-only" => options.count_only = true,
+                "--list-unmapped-formes" => options.list_unmapped_formes = true,
+                "--dump-tms" => options.dump_tms = true,
+                "--dump-raw-stats" => options.dump_raw_stats = true,
+                "--no-skip-egg" => options.no_skip_egg = true,
+                "--split-moves" => options.split_moves = true,
+                "--emit-names" => options.emit_names = true,
+                "--dry-run" => options.dry_run = true,
+                "--force" => options.force = true,
+                "--rebuild" => options.rebuild = true,
+                "--species-full" => options.species_full = true,
+                "--formats-data" => options.formats_data = true,
+                "--default-tier" => options.default_tier = Some(next_value(&mut args, &arg)?),
+                "--flatten-formes" => options.flatten_formes = true,
+                "--compact-formes" => options.compact_formes = true,
+                "--validate-only" => options.validate_only = true,
+                "--manifest" => options.manifest = true,
+                "--meta" => options.meta = true,
+                "--ability-flags" => options.ability_flags = true,
+                "--out-format" => {
+                    options.format = match next_value(&mut args, &arg)?.as_str() {
+                        "json" => OutputFormat::Json,
+                        "showdown-ts-import" => OutputFormat::ShowdownTs,
+                        #[cfg(feature = "msgpack")]
+                        "msgpack" => OutputFormat::MsgPack,
+                        #[cfg(not(feature = "msgpack"))]
+                        "msgpack" => bail!("msgpack output needs the msgpack feature"),
+                        format => bail!("unknown output format {format}"),
+                    }
+                }
+                "--verify-keys" => {
+                    let value = next_value(&mut args, &arg)?;
+                    let Some((line, expected)) = value.split_once('=') else {
+                        bail!("--verify-keys expects <line>=<name>, e.g. 1=Bulbasaur");
+                    };
+                    let line = line
+                        .parse()
+                        .map_err(|e| eyre!("--verify-keys line {line}: {e}"))?;
+                    options.verify_keys = Some((line, expected.to_owned()));
+                }
+                "--rom-type" => {
+                    options.rom_type = match next_value(&mut args, &arg)?.as_str() {
+                        "sm" => RomType::SunMoon,
+                        "usum" => RomType::UltraSunMoon,
+                        rom_type => bail!("unknown rom type {rom_type}"),
+                    }
+                }
+                "--learnset-format" => {
+                    options.learnset_format = match next_value(&mut args, &arg)?.as_str() {
+                        "list" => LearnsetFormat::List,
+                        "showdown" => LearnsetFormat::Showdown,
+                        format => bail!("unknown learnset format {format}"),
+                    }
+                }
+                "--languages" => {
+                    for language in next_value(&mut args, &arg)?.split(',') {
+                        let Some(language) = Language::from_code(language) else {
+                            bail!("unknown language {language}");
+                        };
+                        options.languages.push(language);
+                    }
+                }
+                "--emit-learnset-sources-gen" => {
+                    let gen = next_value(&mut args, &arg)?;
+                    match gen.parse::<u8>() {
+                        Ok(gen @ 1..=7) => options.learnset_sources_from = Some(gen),
+                        _ => bail!("--emit-learnset-sources-gen expects a generation, got {gen}"),
+                    }
+                }
+                "--indent" => {
+                    options.indent = Some(match next_value(&mut args, &arg)?.as_str() {
+                        "tab" => "\t".to_owned(),
+                        n => match n.parse::<usize>() {
+                            Ok(n) => " ".repeat(n),
+                            Err(_) => bail!("--indent expects a number or tab, got {n}"),
+                        },
+                    })
+                }
+                "--only" => {
+                    for output in next_value(&mut args, &arg)?.split(',') {
+                        if !ONLY_OUTPUTS.contains(&output) {
+                            bail!("unknown output {output}, expected one of {ONLY_OUTPUTS:?}");
+                        }
+                        options.only.push(output.to_owned());
+                    }
+                }
+                "--extract-garc" => {
+                    let garc_path = next_value(&mut args, &arg)?.into();
+                    let out_dir = next_value(&mut args, &arg)?.into();
+                    options.extract_garc = Some((garc_path, out_dir));
+                }
+                "--check-typechart" => {
+                    options.check_typechart = Some(next_value(&mut args, &arg)?.into())
+                }
+                "--check-ids-against" => {
+                    options.check_ids_against = Some(next_value(&mut args, &arg)?.into())
+                }
+                flag if flag.starts_with('-') => bail!("unknown option {flag}"),
+                _ => positional.push(arg),
+            }
+        }
+
+        if positional.is_empty() {
+            positional = config_positional;
+        }
+        match positional.as_slice() {
+            [] if options.extract_garc.is_some() => {}
+            [rom_path] if !options.needs_out_path() => options.rom_path = rom_path.into(),
+            [rom_path, out_path] => {
+                options.rom_path = rom_path.into();
+                options.out_path = out_path.into();
+            }
+            _ => bail!("usage: usum-extractor <rom path> <out path> [options]"),
+        }
+        Ok(options)
+    }
+
+    /// Text table indices of the language being dumped
+    pub fn text_ids(&self) -> &'static TextIds {
+        self.language.map_or(&TextIds::ENGLISH, Language::text_ids)
+    }
+
+    /// Number of leading ROM slots left out of the outputs
+    pub fn skipped_slots(&self) -> usize {
+        if self.no_skip_egg {
+            0
+        } else {
+            1
+        }
+    }
+
+    /// Whether the selected mode writes any output
+    pub fn needs_out_path(&self) -> bool {
+        !self.count_only
+            && !self.dry_run
+            && self.check_typechart.is_none()
+            && self.verify_keys.is_none()
+    }
+}
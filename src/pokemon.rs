@@ -1,18 +1,19 @@
 use crate::{
-    garc::{self, GarcFile},
-    garc_files,
+    garc, garc_files,
+    options::{Options, RomType},
     text::TextFile,
-    text_ids, to_id, PokemonStats, Stats,
+    to_id, PokemonStats, RomData, Stats, GENDER_DIFFERENCE_BIT,
 };
 use binrw::{until_eof, BinRead};
 use color_eyre::Result;
 use indexmap::{IndexMap, IndexSet};
-use serde::Serialize;
-use std::{collections::BTreeMap, fs::File, io::Write, path::Path};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs::File, io::BufReader, path::Path};
+use tracing::{debug, info, warn};
 
 #[allow(dead_code)]
 #[derive(BinRead, Debug)]
-struct PokemonEvolution {
+pub(crate) struct PokemonEvolution {
     method: u16,
     argument: u16,
     species: u16,
@@ -20,6 +21,9 @@ struct PokemonEvolution {
     level: u8,
 }
 
+/// The evolutions of a species, unused slots are zeroed
+pub(crate) type Evolutions = [PokemonEvolution; 8];
+
 #[allow(dead_code)]
 #[derive(BinRead, Debug)]
 struct PokemonMegaEvolution {
@@ -30,45 +34,63 @@ struct PokemonMegaEvolution {
 }
 
 #[derive(BinRead, Debug)]
-struct PokemonMegaEvolutions {
+pub(crate) struct PokemonMegaEvolutions {
     #[br(parse_with = until_eof)]
     mega_evos: Vec<PokemonMegaEvolution>,
 }
 
 #[allow(non_snake_case)]
-#[derive(Serialize, Debug)]
-struct PokemonJsGenderRatio {
-    M: f32,
-    F: f32,
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PokemonJsGenderRatio {
+    pub M: f32,
+    pub F: f32,
+}
+
+/// Formes a battle-only forme can be reached from
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(untagged)]
+pub enum PokemonJsBattleOnly {
+    One(String),
+    Many(Vec<String>),
 }
 
 #[allow(non_snake_case)]
 #[serde_with::skip_serializing_none]
-#[derive(Serialize, Debug)]
-struct PokemonJs {
-    num: u32,
-    name: String,
-    types: Vec<String>,
-    gender: Option<String>,
-    genderRatio: Option<PokemonJsGenderRatio>,
-    baseStats: Stats,
-    abilities: BTreeMap<String, String>,
-    weightkg: f32,
-
-    prevo: Option<String>,
-    evoLevel: Option<u16>,
-    evoType: Option<String>,
-    evoItem: Option<String>,
-    evoCondition: Option<String>,
-    evos: Option<Vec<String>>,
-    eggGroups: Vec<String>,
-
-    baseSpecies: Option<String>,
-    forme: Option<String>,
-    formes: Option<Vec<String>>,
-    requiredItems: Option<Vec<String>>,
-
-    unusable: Option<bool>,
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PokemonJs {
+    pub num: u32,
+    pub name: String,
+    pub types: Vec<String>,
+    pub gender: Option<String>,
+    pub genderRatio: Option<PokemonJsGenderRatio>,
+    pub baseStats: Stats,
+    pub abilities: BTreeMap<String, String>,
+    pub weightkg: f32,
+
+    pub prevo: Option<String>,
+    pub evoLevel: Option<u16>,
+    pub evoType: Option<String>,
+    pub evoItem: Option<String>,
+    pub evoMove: Option<String>,
+    pub evoCondition: Option<String>,
+    pub evos: Option<Vec<String>>,
+    pub eggGroups: Vec<String>,
+    pub heldItems: Option<Vec<String>>,
+    pub genderDifferences: Option<bool>,
+
+    pub baseSpecies: Option<String>,
+    pub forme: Option<String>,
+    pub baseForme: Option<String>,
+    pub formes: Option<Vec<String>>,
+    pub cosmeticFormes: Option<Vec<String>>,
+    pub requiredItems: Option<Vec<String>>,
+    pub requiredAbility: Option<String>,
+    pub requiredMove: Option<String>,
+    pub battleOnly: Option<PokemonJsBattleOnly>,
+
+    pub unusable: Option<bool>,
+    pub unreleasedHidden: Option<bool>,
+    pub isNonstandard: Option<String>,
 }
 
 const FORME_NAMES: &[((&str, usize), &str)] = &[
@@ -81,6 +103,13 @@ const FORME_NAMES: &[((&str, usize), &str)] = &[
     (("Rattata", 1), "Alola"),
     (("Raticate", 1), "Alola"),
     (("Raticate", 2), "Alola-Totem"),
+    (("Pikachu", 1), "Original"),
+    (("Pikachu", 2), "Hoenn"),
+    (("Pikachu", 3), "Sinnoh"),
+    (("Pikachu", 4), "Unova"),
+    (("Pikachu", 5), "Kalos"),
+    (("Pikachu", 6), "Alola"),
+    (("Pikachu", 7), "Partner"),
     (("Raichu", 1), "Alola"),
     (("Sandshrew", 1), "Alola"),
     (("Sandslash", 1), "Alola"),
@@ -102,7 +131,7 @@ const FORME_NAMES: &[((&str, usize), &str)] = &[
     (("Slowpoke", 1), "Galar"),
     (("Slowbro", 1), "Mega"),
     (("Slowbro", 2), "Galar"),
-    (("Farfetch’d", 1), "Galar"),
+    (("Farfetch'd", 1), "Galar"),
     (("Grimer", 1), "Alola"),
     (("Muk", 1), "Alola"),
     (("Gengar", 1), "Mega"),
@@ -255,8 +284,9 @@ const FORME_NAMES: &[((&str, usize), &str)] = &[
     (("Gourgeist", 3), "Super"),
     (("Avalugg", 1), "Hisui"),
     (("Xerneas", 1), "Neutral"),
-    (("Zygarde", 2), "10%"),
-    (("Zygarde", 3), "50%"),
+    // Forms 2 and 3 are the Power Construct 10% and 50% duplicates, the base species stands
+    // for 50%
+    (("Zygarde", 1), "10%"),
     (("Zygarde", 4), "Complete"),
     (("Diancie", 1), "Mega"),
     (("Hoopa", 1), "Unbound"),
@@ -307,6 +337,80 @@ const FORME_NAMES: &[((&str, usize), &str)] = &[
     (("Necrozma", 3), "Ultra"),
 ];
 
+type FormeTypes = (&'static str, Option<&'static str>);
+
+/// Types of the Arceus and Silvally formes, which only get their type from the held
+/// Plate/Memory in battle. This isn't checked against a retail dump: the forme stats
+/// entries are expected to carry the forme's type already, so on those the table changes
+/// nothing. It guards dumps and romhacks whose entries keep the base species' types.
+const FORME_TYPES: &[((&str, usize), FormeTypes)] = &[
+    (("Arceus", 1), ("Bug", None)),
+    (("Arceus", 2), ("Dark", None)),
+    (("Arceus", 3), ("Dragon", None)),
+    (("Arceus", 4), ("Electric", None)),
+    (("Arceus", 5), ("Fairy", None)),
+    (("Arceus", 6), ("Fighting", None)),
+    (("Arceus", 7), ("Fire", None)),
+    (("Arceus", 8), ("Flying", None)),
+    (("Arceus", 9), ("Ghost", None)),
+    (("Arceus", 10), ("Grass", None)),
+    (("Arceus", 11), ("Ground", None)),
+    (("Arceus", 12), ("Ice", None)),
+    (("Arceus", 13), ("Poison", None)),
+    (("Arceus", 14), ("Psychic", None)),
+    (("Arceus", 15), ("Rock", None)),
+    (("Arceus", 16), ("Steel", None)),
+    (("Arceus", 17), ("Water", None)),
+    (("Silvally", 1), ("Bug", None)),
+    (("Silvally", 2), ("Dark", None)),
+    (("Silvally", 3), ("Dragon", None)),
+    (("Silvally", 4), ("Electric", None)),
+    (("Silvally", 5), ("Fairy", None)),
+    (("Silvally", 6), ("Fighting", None)),
+    (("Silvally", 7), ("Fire", None)),
+    (("Silvally", 8), ("Flying", None)),
+    (("Silvally", 9), ("Ghost", None)),
+    (("Silvally", 10), ("Grass", None)),
+    (("Silvally", 11), ("Ground", None)),
+    (("Silvally", 12), ("Ice", None)),
+    (("Silvally", 13), ("Poison", None)),
+    (("Silvally", 14), ("Psychic", None)),
+    (("Silvally", 15), ("Rock", None)),
+    (("Silvally", 16), ("Steel", None)),
+    (("Silvally", 17), ("Water", None)),
+];
+
+fn get_forme_types(species: &str, forme_id: usize) -> Option<Vec<String>> {
+    FORME_TYPES
+        .iter()
+        .find(|((name, id), _)| **name == *species && *id == forme_id)
+        .map(|(_, (first, second))| {
+            [Some(*first), *second]
+                .iter()
+                .flatten()
+                .map(|t| (*t).to_owned())
+                .collect()
+        })
+}
+
+/// Formes that only differ visually, they are listed on the base species instead of
+/// getting their own entry
+const COSMETIC_FORMES: &[(&str, usize)] = &[
+    ("Pikachu", 1),
+    ("Pikachu", 2),
+    ("Pikachu", 3),
+    ("Pikachu", 4),
+    ("Pikachu", 5),
+    ("Pikachu", 6),
+    ("Pikachu", 7),
+];
+
+fn is_cosmetic_forme(species: &str, forme_id: usize) -> bool {
+    COSMETIC_FORMES
+        .iter()
+        .any(|(name, id)| *name == species && *id == forme_id)
+}
+
 fn get_forme_name(species: &str, forme_id: usize) -> Option<String> {
     FORME_NAMES
         .iter()
@@ -314,93 +418,247 @@ fn get_forme_name(species: &str, forme_id: usize) -> Option<String> {
         .map(|(_, forme_name)| (*forme_name).to_owned())
 }
 
-pub fn dump_pokes(
-    rom_path: &Path,
-    out_path: &Path,
-    text_files: &[TextFile],
-) -> Result<BTreeMap<usize, String>> {
-    const NORMAL_FORME_COUNT: usize = 808;
-    let mut dex_map: BTreeMap<usize, PokemonJs> = BTreeMap::new();
+/// Unused slots have no name or all-zero stats
+fn is_placeholder_species(pokemon: &PokemonStats, name: &str) -> bool {
+    name.trim().is_empty() || pokemon.stats.iter().all(|(_, s)| s == 0)
+}
+
+pub(crate) fn read_evolutions(rom_path: &Path) -> Result<Vec<Evolutions>> {
+    let evo_path = rom_path
+        .join(garc_files::BASE_PATH)
+        .join(garc_files::EVOLUTIONS);
+    garc::read_files(&garc::open(&evo_path)?)
+}
 
-    let pokemon_path = rom_path
+pub(crate) fn read_mega_evos(rom_path: &Path) -> Result<Vec<PokemonMegaEvolutions>> {
+    let mega_evo_path = rom_path
         .join(garc_files::BASE_PATH)
-        .join(garc_files::POKEMON_STATS);
-    let pokemons =
-        garc::read_files::<PokemonStats>(&GarcFile::read_le(&mut File::open(pokemon_path)?)?);
+        .join(garc_files::MEGA_EVOS);
+    garc::read_files(&garc::open(&mega_evo_path)?)
+}
+
+const NORMAL_FORME_COUNT: usize = 808;
 
-    let species_names = &text_files[text_ids::SPECIES_NAMES].lines;
-    let ability_names = &text_files[text_ids::ABILITY_NAMES].lines;
-    let type_names = &text_files[text_ids::TYPE_NAMES].lines;
-    let item_names = &text_files[text_ids::ITEM_NAMES].lines;
-    let move_names = &text_files[text_ids::MOVE_NAMES].lines;
+/// A personal data index that gets a pokedex entry
+struct SpeciesSlot {
+    index: usize,
+    name: String,
+    /// Base species index and forme name of a forme
+    forme: Option<(usize, String)>,
+}
+
+/// The species and formes of the pokedex, in personal data order, and the cosmetic formes
+/// of each base species
+fn species_slots(
+    pokemons: &[PokemonStats],
+    species_names: &[String],
+    options: &Options,
+) -> (Vec<SpeciesSlot>, BTreeMap<usize, Vec<String>>) {
+    let mut slots = Vec::new();
+    let mut cosmetic_formes: BTreeMap<usize, Vec<String>> = BTreeMap::new();
 
     for (index, pokemon) in pokemons.iter().take(NORMAL_FORME_COUNT).enumerate() {
         let name = &species_names[index];
-        let poke = make_poke(pokemon, type_names, ability_names, index, name);
-        dex_map.insert(index, poke);
+        // Slot 0 is the Egg, it's skipped separately
+        if index > 0 && is_placeholder_species(pokemon, name) {
+            warn!("skipping placeholder species {index} ({name:?})");
+            continue;
+        }
+        slots.push(SpeciesSlot {
+            index,
+            name: name.clone(),
+            forme: None,
+        });
     }
 
     for (base_index, pokemon) in pokemons.iter().take(NORMAL_FORME_COUNT).enumerate() {
-        if pokemon.form_count <= 1 || (pokemon.form_stats_id as usize) < NORMAL_FORME_COUNT {
+        if pokemon.form_count <= 1 {
             continue;
         }
         let base_name = &species_names[base_index];
-        let mut formes: Vec<String> = vec![base_name.to_owned()];
+        // Formes with their own personal data are stored after the species. Without one
+        // (Unown, Vivillon, ...) they only differ in appearance.
+        if pokemon.form_stats_id == 0 {
+            debug!(
+                "{base_name} has {} formes without personal data",
+                pokemon.form_count
+            );
+            continue;
+        }
+        if (pokemon.form_stats_id as usize) < NORMAL_FORME_COUNT {
+            warn!(
+                "{base_name} form_stats_id {} points inside the species range, skipping its formes",
+                pokemon.form_stats_id
+            );
+            continue;
+        }
         for form_id in 1..pokemon.form_count {
             let index = pokemon.form_stats_id as usize + form_id as usize - 1;
             let Some(forme_name) = get_forme_name(base_name, form_id as _) else {
+                debug!("skipping unmapped forme {base_name} {form_id}");
+                if options.list_unmapped_formes {
+                    println!(
+                        "unmapped forme: ((\"{base_name}\", {form_id}), _) form_stats_id {}",
+                        pokemon.form_stats_id
+                    );
+                }
                 continue;
             };
             let name = format!("{}-{}", base_name, forme_name);
-            formes.push(name.clone());
-            let pokemon_forme = &pokemons[index];
-            let mut poke = make_poke(pokemon_forme, type_names, ability_names, index, &name);
-            poke.num = base_index as _;
-            poke.forme = Some(forme_name.to_owned());
+            if is_cosmetic_forme(base_name, form_id as _) {
+                cosmetic_formes.entry(base_index).or_default().push(name);
+                continue;
+            }
+            if is_placeholder_species(&pokemons[index], &name) {
+                warn!("skipping placeholder forme {index} ({name:?})");
+                continue;
+            }
+            slots.push(SpeciesSlot {
+                index,
+                name,
+                forme: Some((base_index, forme_name)),
+            });
+        }
+    }
+    (slots, cosmetic_formes)
+}
+
+/// The name of each personal data index with a pokedex entry, which is all the learnsets
+/// need, without building the pokedex
+pub fn build_name_map(
+    pokemons: &[PokemonStats],
+    text_files: &[TextFile],
+    options: &Options,
+) -> BTreeMap<usize, String> {
+    let species_names = &text_files[options.text_ids().species_names].lines;
+    let (slots, _) = species_slots(pokemons, species_names, options);
+    slots
+        .into_iter()
+        .map(|slot| (slot.index, slot.name))
+        .collect()
+}
+
+/// The pokedex by id, and the name of each personal data index for the learnsets
+pub fn build_pokedex(
+    rom: &RomData,
+    text_files: &[TextFile],
+    options: &Options,
+) -> Result<(IndexMap<String, PokemonJs>, BTreeMap<usize, String>)> {
+    let pokemons = rom.pokemons()?;
+    let mut dex_map: BTreeMap<usize, PokemonJs> = BTreeMap::new();
+
+    let ids = options.text_ids();
+    let species_names = &text_files[ids.species_names].lines;
+    let ability_names = &text_files[ids.ability_names].lines;
+    let type_names = &text_files[ids.type_names].lines;
+    let item_names = &text_files[ids.item_names].lines;
+    let move_names = &text_files[ids.move_names].lines;
+
+    let (slots, mut cosmetic_formes) = species_slots(pokemons, species_names, options);
+    let mut formes: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+    for slot in &slots {
+        let mut poke = make_poke(
+            &pokemons[slot.index],
+            type_names,
+            ability_names,
+            item_names,
+            slot.index,
+            &slot.name,
+        );
+        if let Some((base_index, forme_name)) = &slot.forme {
+            let base_name = &species_names[*base_index];
+            let form_id = slot.index + 1 - pokemons[*base_index].form_stats_id as usize;
+            if let Some(types) = get_forme_types(base_name, form_id) {
+                poke.types = types;
+            }
+            // Weight-based moves break on 0, a forme without one weighs as much as its base
+            if poke.weightkg == 0. {
+                let base_weight = pokemons[*base_index].weight as f32 / 10.;
+                let name = &slot.name;
+                debug!("{name} has no weight, using the {base_weight} of {base_name}");
+                poke.weightkg = base_weight;
+            }
+            poke.num = *base_index as _;
+            poke.forme = Some(forme_name.clone());
             poke.baseSpecies = Some(base_name.clone());
-            dex_map.insert(index, poke);
+            formes
+                .entry(*base_index)
+                .or_insert_with(|| vec![base_name.clone()])
+                .push(slot.name.clone());
+        }
+        // Cosmoem and Celesteela, the heaviest species, are 999.9 kg. Slot 0 is the Egg.
+        let weight = poke.weightkg;
+        if slot.index > 0 && (weight == 0. || weight > 999.9) {
+            warn!("{} has an unlikely weight of {weight} kg", slot.name);
         }
+        dex_map.insert(slot.index, poke);
+    }
 
+    for (base_index, formes) in formes {
         if let Some(dex) = dex_map.get_mut(&base_index) {
-            dex.formes = Some(formes.clone())
+            dex.formes = Some(formes.clone());
+            dex.cosmeticFormes = cosmetic_formes.remove(&base_index);
         }
-        for form_id in 1..pokemon.form_count {
-            let index = pokemon.form_stats_id as usize + form_id as usize - 1;
-            if let Some(dex) = dex_map.get_mut(&index) {
+        let form_stats_id = pokemons[base_index].form_stats_id as usize;
+        for form_id in 1..pokemons[base_index].form_count as usize {
+            if let Some(dex) = dex_map.get_mut(&(form_stats_id + form_id - 1)) {
                 dex.formes = Some(formes.clone());
             }
         }
     }
+    for (base_index, cosmetic_formes) in cosmetic_formes {
+        if let Some(dex) = dex_map.get_mut(&base_index) {
+            dex.cosmeticFormes = Some(cosmetic_formes);
+        }
+    }
 
-    let evo_path = rom_path
-        .join(garc_files::BASE_PATH)
-        .join(garc_files::EVOLUTIONS);
-    let evolutions =
-        garc::read_files::<[PokemonEvolution; 8]>(&GarcFile::read_le(&mut File::open(evo_path)?)?);
-    handle_evos(evolutions, item_names, move_names, &mut dex_map, &pokemons);
+    handle_evos(
+        rom.evolutions()?,
+        item_names,
+        move_names,
+        species_names,
+        type_names,
+        &mut dex_map,
+        pokemons,
+    );
 
-    let mega_evo_path = rom_path
-        .join(garc_files::BASE_PATH)
-        .join(garc_files::MEGA_EVOS);
-    let mega_evos = garc::read_files::<PokemonMegaEvolutions>(&GarcFile::read_le(
-        &mut File::open(mega_evo_path)?,
-    )?);
-    handle_mega_evos(mega_evos, item_names, &mut dex_map, &pokemons);
+    handle_mega_evos(
+        rom.mega_evos()?,
+        item_names,
+        move_names,
+        &mut dex_map,
+        pokemons,
+    );
 
     let name_map = dex_map.iter().map(|(i, s)| (*i, s.name.clone())).collect();
 
-    let mut sorted_dex_list: Vec<_> = dex_map.into_values().collect();
-    sorted_dex_list.sort_by(|l, r| l.num.cmp(&r.num));
+    // Base species first, then its formes by form_id, which is also their stats index order
+    let mut sorted_dex_list: Vec<_> = dex_map.into_iter().collect();
+    sorted_dex_list.sort_by_key(|(index, dex)| (dex.num, *index));
     let mut dex_map: IndexMap<String, PokemonJs> = sorted_dex_list
         .into_iter()
-        .skip(1) // Skip Egg
-        .map(|dex| (to_id(dex.name.clone()), dex))
+        .skip(options.skipped_slots()) // Skip Egg
+        .map(|(_, dex)| (to_id(dex.name.clone()), dex))
         .collect();
-    manual_patches(&mut dex_map);
+    info!("applying pokedex manual patches");
+    manual_patches(&mut dex_map, options.rom_type);
+    if options.debug {
+        check_known_formes(&dex_map);
+    }
+    if options.compact_formes {
+        compact_formes(&mut dex_map);
+    }
+    if options.flatten_formes {
+        // Formes already have their own stats, types and abilities from their personal data
+        for dex in dex_map.values_mut() {
+            dex.baseSpecies = None;
+            dex.baseForme = None;
+            dex.formes = None;
+            dex.cosmeticFormes = None;
+        }
+    }
 
-    let mut f = File::create(out_path.join("pokedex.json"))?;
-    write!(f, "{}", serde_json::to_string_pretty(&dex_map)?)?;
-    Ok(name_map)
+    Ok((dex_map, name_map))
 }
 
 const EGG_GROUPS: &[&str] = &[
@@ -422,10 +680,37 @@ const EGG_GROUPS: &[&str] = &[
     "Undiscovered",
 ];
 
+/// Ratios of the personal data gender byte Showdown writes exactly, the formula is off for 1:7
+const GENDER_RATIOS: &[(u8, f32, f32)] = &[
+    (31, 0.875, 0.125),
+    (63, 0.75, 0.25),
+    (127, 0.5, 0.5),
+    (191, 0.25, 0.75),
+    (225, 0.125, 0.875),
+];
+
+/// Showdown's `gender` and `genderRatio` for a personal data gender byte
+pub fn gender_ratio(gender: u8) -> (Option<String>, Option<PokemonJsGenderRatio>) {
+    match gender {
+        0 => (Some("M".to_owned()), None),
+        254 => (Some("F".to_owned()), None),
+        255 => (Some("N".to_owned()), None),
+        g => {
+            let (m, f) = GENDER_RATIOS
+                .iter()
+                .find(|(byte, _, _)| *byte == g)
+                .map(|(_, m, f)| (*m, *f))
+                .unwrap_or(((256. - (g as f32 + 1.)) / 256., (g as f32 + 1.) / 256.));
+            (None, Some(PokemonJsGenderRatio { M: m, F: f }))
+        }
+    }
+}
+
 fn make_poke(
     pokemon: &PokemonStats,
     type_names: &[String],
     ability_names: &[String],
+    item_names: &[String],
     index: usize,
     name: &str,
 ) -> PokemonJs {
@@ -456,25 +741,29 @@ fn make_poke(
         );
     }
 
+    // Single group species store it twice, e.g. legendaries are (15, 15) for Undiscovered.
+    // Group 0 is a placeholder that never belongs in the output.
     let mut egg_groups: Vec<String> = pokemon
         .egg_groups
         .iter()
+        .filter(|id| **id != 0)
         .map(|id| EGG_GROUPS[*id as usize].to_owned())
         .collect();
     egg_groups.dedup();
+    if egg_groups.is_empty() {
+        egg_groups.push("Undiscovered".to_owned());
+    }
 
-    let (gender, gender_ratio) = match pokemon.gender {
-        0 => (Some("M".to_owned()), None),
-        254 => (Some("F".to_owned()), None),
-        255 => (Some("N".to_owned()), None),
-        g => (
-            None,
-            Some(PokemonJsGenderRatio {
-                M: (256. - (g + 1) as f32) / 256.,
-                F: ((g + 1) as f32 / 256.),
-            }),
-        ),
-    };
+    // Wild held items, in 50% / 5% / 1% slot order
+    let mut held_items: Vec<String> = pokemon
+        .items
+        .iter()
+        .filter(|id| **id != 0)
+        .map(|id| item_names[*id as usize].clone())
+        .collect();
+    held_items.dedup();
+
+    let (gender, gender_ratio) = gender_ratio(pokemon.gender);
 
     PokemonJs {
         num: index as _,
@@ -489,21 +778,33 @@ fn make_poke(
         evoType: None,
         evoLevel: None,
         evoItem: None,
+        evoMove: None,
         evoCondition: None,
         evos: None,
         eggGroups: egg_groups,
+        heldItems: (!held_items.is_empty()).then_some(held_items),
+        genderDifferences: (pokemon.sprite_bits & GENDER_DIFFERENCE_BIT != 0).then_some(true),
         baseSpecies: None,
         forme: None,
+        baseForme: None,
         formes: None,
+        cosmeticFormes: None,
         requiredItems: None,
+        requiredAbility: None,
+        requiredMove: None,
+        battleOnly: None,
         unusable: None,
+        unreleasedHidden: None,
+        isNonstandard: None,
     }
 }
 
 fn handle_evos(
-    evolutions: Vec<[PokemonEvolution; 8]>,
+    evolutions: &[Evolutions],
     item_names: &[String],
     move_names: &[String],
+    species_names: &[String],
+    type_names: &[String],
     dex_map: &mut BTreeMap<usize, PokemonJs>,
     pokemons: &[PokemonStats],
 ) {
@@ -564,30 +865,64 @@ fn handle_evos(
                     poke_entry.evoItem = Some(item_names[evo.argument as usize].clone());
                     poke_entry.evoCondition = Some("on female".to_owned());
                 }
+                // Level-up while holding the argument item, 19 during the day and 20 during
+                // the night. Gen 7 has no held item method without a time of day, a species
+                // with both loses the condition.
                 19 => {
                     poke_entry.evoType = Some("levelHold".to_owned());
                     poke_entry.evoItem = Some(item_names[evo.argument as usize].clone());
-                    if poke_entry.evoCondition == Some("in the night".to_owned()) {
+                    if poke_entry.evoCondition == Some("during the night".to_owned()) {
                         poke_entry.evoCondition = None;
                     } else {
-                        poke_entry.evoCondition = Some("in the day".to_owned());
+                        poke_entry.evoCondition = Some("during the day".to_owned());
                     }
                 }
                 20 => {
                     poke_entry.evoType = Some("levelHold".to_owned());
                     poke_entry.evoItem = Some(item_names[evo.argument as usize].clone());
-                    if poke_entry.evoCondition == Some("in the day".to_owned()) {
+                    if poke_entry.evoCondition == Some("during the day".to_owned()) {
                         poke_entry.evoCondition = None;
                     } else {
-                        poke_entry.evoCondition = Some("in the night".to_owned());
+                        poke_entry.evoCondition = Some("during the night".to_owned());
                     }
                 }
+                // The argument is a move id
                 21 => {
+                    poke_entry.evoType = Some("levelMove".to_owned());
+                    poke_entry.evoMove = Some(move_names[evo.argument as usize].clone());
+                }
+                // The argument is a species id
+                22 => {
+                    poke_entry.evoType = Some("levelExtra".to_owned());
+                    poke_entry.evoCondition = Some(format!(
+                        "with a {} in party",
+                        species_names[evo.argument as usize]
+                    ));
+                }
+                // The argument is a type id
+                29 => {
+                    poke_entry.evoType = Some("levelExtra".to_owned());
+                    poke_entry.evoCondition = Some(format!(
+                        "with a {}-type move and two levels of Affection",
+                        type_names[evo.argument as usize]
+                    ));
+                }
+                30 => {
+                    poke_entry.evoCondition = Some(format!(
+                        "with a {}-type in the party",
+                        type_names[evo.argument as usize]
+                    ));
+                }
+                // Plain level up, Showdown's default
+                4 => {}
+                // Not mapped yet, kept visible in the output instead of passing for a level up
+                method => {
                     poke_entry.evoType = Some("other".to_owned());
-                    poke_entry.evoCondition = Some(format!("level-up with {}", move_names[evo.argument as usize].clone()));
+                    poke_entry.evoCondition = Some(format!(
+                        "evolution method {method} with argument {}",
+                        evo.argument
+                    ));
                 }
-
-                _ => {}
             }
         }
         if !evo_set.is_empty() {
@@ -596,22 +931,29 @@ fn handle_evos(
     }
 }
 
+/// Method 1 megas need their stone as `requiredItems`, method 2 (Rayquaza) its move as
+/// `requiredMove`
 fn handle_mega_evos(
-    mega_evos_list: Vec<PokemonMegaEvolutions>,
+    mega_evos_list: &[PokemonMegaEvolutions],
     item_names: &[String],
+    move_names: &[String],
     dex_map: &mut BTreeMap<usize, PokemonJs>,
     pokemons: &[PokemonStats],
 ) {
     for (index, mega_evos) in mega_evos_list.iter().enumerate() {
         let base_poke = &pokemons[index];
         for mega_evo in &mega_evos.mega_evos {
-            if mega_evo.method != 1 {
+            if mega_evo.method != 1 && mega_evo.method != 2 {
                 continue;
             }
             let new_forme_id = (base_poke.form_stats_id + mega_evo.forme - 1) as usize;
             let Some(new_forme) = dex_map.get_mut(&new_forme_id) else {
                 continue;
             };
+            if mega_evo.method == 2 {
+                new_forme.requiredMove = Some(move_names[mega_evo.argument as usize].clone());
+                continue;
+            }
             let mut required_items = new_forme.requiredItems.clone().unwrap_or_default();
             required_items.push(item_names[mega_evo.argument as usize].clone());
             new_forme.requiredItems = Some(required_items);
@@ -636,19 +978,107 @@ pub const UNUSABLES: &[&str] = &[
     "reshiram",
     "xerneas",
     "yveltal",
-    "zygardecomplete",
 ];
 
-const REMOVE: &[&str] = &[
-    "pumpkaboosmall",
-    "pumpkaboolarge",
-    "pumpkaboosuper",
-    "gourgeist",
-    "gourgeistlarge",
-    "zygarde",
+/// Species whose hidden ability was never distributed, per rom type. The ability slots don't
+/// carry release status, so this mirrors the `unreleasedHidden` entries of Showdown's gen 7
+/// species data (data/mods/gen7). The Alola starters only got theirs in Sword/Shield.
+const UNRELEASED_HIDDEN: &[(RomType, &[&str])] = &[
+    (RomType::SunMoon, ALOLA_STARTERS),
+    (RomType::UltraSunMoon, ALOLA_STARTERS),
+];
+
+const ALOLA_STARTERS: &[&str] = &[
+    "rowlet",
+    "dartrix",
+    "decidueye",
+    "litten",
+    "torracat",
+    "incineroar",
+    "popplio",
+    "brionne",
+    "primarina",
+];
+
+/// Formes changed into by a move outside the mega evolution table. Rotom's appliance
+/// formes change its move but don't need one, and Necrozma-Ultra needs an item.
+const REQUIRED_MOVES: &[(&str, &str)] = &[
+    ("keldeoresolute", "Secret Sword"),
+    ("meloettapirouette", "Relic Song"),
+];
+
+/// Name of the default forme of species whose formes have one in Showdown
+const BASE_FORMES: &[(&str, &str)] = &[
+    ("deoxys", "Normal"),
+    ("wormadam", "Plant"),
+    ("giratina", "Altered"),
+    ("shaymin", "Land"),
+    ("arceus", "Normal"),
+    ("basculin", "Red-Striped"),
+    ("darmanitan", "Standard"),
+    ("tornadus", "Incarnate"),
+    ("thundurus", "Incarnate"),
+    ("landorus", "Incarnate"),
+    ("keldeo", "Ordinary"),
+    ("meloetta", "Aria"),
+    ("vivillon", "Meadow"),
+    ("pumpkaboo", "Average"),
+    ("gourgeist", "Average"),
+    ("aegislash", "Shield"),
+    ("hoopa", "Confined"),
+    ("oricorio", "Baile"),
+    ("lycanroc", "Midday"),
+    ("wishiwashi", "Solo"),
+    ("silvally", "Normal"),
+    ("minior", "Meteor"),
+    ("mimikyu", "Disguised"),
+    ("zygarde", "50%"),
 ];
 
-fn manual_patches(dex_map: &mut IndexMap<String, PokemonJs>) {
+/// Formes split by gender. The ROM gives both the species' gender ratio, Showdown makes the
+/// base species male and the forme female.
+const GENDERED_FORMES: &[(&str, &str)] = &[("meowstic", "M"), ("meowsticf", "F")];
+
+/// Folds formes with the stats, types and abilities of their base species into its
+/// `cosmeticFormes`, unless they need something to be used or are marked nonstandard
+fn compact_formes(dex_map: &mut IndexMap<String, PokemonJs>) {
+    let cosmetic: Vec<(String, String)> = dex_map
+        .iter()
+        .filter_map(|(id, dex)| {
+            let base_id = to_id(dex.baseSpecies.clone()?);
+            let base = dex_map.get(&base_id)?;
+            let same = dex.baseStats == base.baseStats
+                && dex.types == base.types
+                && dex.abilities == base.abilities;
+            let distinct = dex.requiredItems.is_some()
+                || dex.requiredAbility.is_some()
+                || dex.requiredMove.is_some()
+                || dex.battleOnly.is_some()
+                || dex.isNonstandard.is_some();
+            (same && !distinct).then(|| (id.clone(), base_id))
+        })
+        .collect();
+    for (id, base_id) in cosmetic {
+        let Some(forme) = dex_map.shift_remove(&id) else {
+            continue;
+        };
+        for dex in dex_map.values_mut() {
+            let Some(formes) = &mut dex.formes else {
+                continue;
+            };
+            formes.retain(|name| *name != forme.name);
+            if formes.len() <= 1 {
+                dex.formes = None;
+            }
+        }
+        dex_map[&base_id]
+            .cosmeticFormes
+            .get_or_insert_with(Vec::new)
+            .push(forme.name);
+    }
+}
+
+fn manual_patches(dex_map: &mut IndexMap<String, PokemonJs>, rom_type: RomType) {
     for unusable in UNUSABLES {
         let Some(entry) = dex_map.get_mut(*unusable) else {
             continue;
@@ -656,25 +1086,73 @@ fn manual_patches(dex_map: &mut IndexMap<String, PokemonJs>) {
         entry.unusable = Some(true);
     }
 
-    for remove in REMOVE {
-        let Some(entry) = dex_map.get_mut(*remove) else {
+    // Totem formes are only fought in the island trials, they keep their boosted stats but
+    // are marked so they aren't taken for selectable formes
+    for entry in dex_map.values_mut() {
+        if entry.forme.as_deref().is_some_and(|f| f.ends_with("Totem")) {
+            entry.isNonstandard = Some("Unobtainable".to_owned());
+        }
+    }
+
+    for (id, base_forme) in BASE_FORMES {
+        let Some(entry) = dex_map.get_mut(*id) else {
             continue;
         };
-        let new_formes: Vec<String> = entry
-            .formes
-            .as_ref()
-            .unwrap()
-            .iter()
-            .filter(|f| to_id((*f).clone()) != *remove)
-            .cloned()
-            .collect();
-        for n in &new_formes {
-            let Some(entry) = dex_map.get_mut(&to_id(n.clone())) else {
-                continue;
-            };
-            entry.formes = Some(new_formes.clone());
+        entry.baseForme = Some((*base_forme).to_owned());
+    }
+
+    let unreleased_hidden = UNRELEASED_HIDDEN
+        .iter()
+        .filter(|(rom, _)| *rom == rom_type)
+        .flat_map(|(_, ids)| ids.iter());
+    for id in unreleased_hidden {
+        let Some(entry) = dex_map.get_mut(*id) else {
+            continue;
+        };
+        if entry.abilities.contains_key("H") {
+            entry.unreleasedHidden = Some(true);
         }
-        dex_map.shift_remove(*remove);
+    }
+
+    for (forme, required_move) in REQUIRED_MOVES {
+        let Some(entry) = dex_map.get_mut(*forme) else {
+            continue;
+        };
+        entry.requiredMove = Some((*required_move).to_owned());
+    }
+
+    for (id, gender) in GENDERED_FORMES {
+        let Some(entry) = dex_map.get_mut(*id) else {
+            continue;
+        };
+        entry.gender = Some((*gender).to_owned());
+        entry.genderRatio = None;
+    }
+
+    // Showdown's Zygarde is the 50% forme, Complete is only reached through Power Construct
+    for id in ["zygarde", "zygarde10"] {
+        let Some(zygarde) = dex_map.get_mut(id) else {
+            continue;
+        };
+        zygarde
+            .abilities
+            .insert("S".to_owned(), "Power Construct".to_owned());
+    }
+    if let Some(complete) = dex_map.get_mut("zygardecomplete") {
+        complete.battleOnly = Some(PokemonJsBattleOnly::Many(vec![
+            "Zygarde".to_owned(),
+            "Zygarde-10%".to_owned(),
+        ]));
+        complete.requiredAbility = Some("Power Construct".to_owned());
+    }
+
+    // Dusk-Mane and Dawn-Wings are regular formes, Ultra Burst only works from them
+    if let Some(ultra) = dex_map.get_mut("necrozmaultra") {
+        ultra.battleOnly = Some(PokemonJsBattleOnly::Many(vec![
+            "Necrozma-Dawn-Wings".to_owned(),
+            "Necrozma-Dusk-Mane".to_owned(),
+        ]));
+        ultra.requiredItems = Some(vec!["Ultranecrozium Z".to_owned()]);
     }
 
     if let Some(porygon_2) = dex_map.get_mut("porygon2") {
@@ -684,3 +1162,55 @@ fn manual_patches(dex_map: &mut IndexMap<String, PokemonJs>) {
         porygon_2.evoCondition = Some("level 35) or Porygon-Z (level-up holding Hotfix at level 40".to_owned());
     }
 }
+
+/// Formes whose types or abilities differ from their base species, as in Showdown
+const KNOWN_FORMES: &[(&str, &[&str], &[&str])] = &[
+    ("oricorio", &["Fire", "Flying"], &["Dancer"]),
+    ("oricoriopompom", &["Electric", "Flying"], &["Dancer"]),
+    ("oricoriopau", &["Psychic", "Flying"], &["Dancer"]),
+    ("oricoriosensu", &["Ghost", "Flying"], &["Dancer"]),
+    (
+        "lycanroc",
+        &["Rock"],
+        &["Keen Eye", "Sand Rush", "Steadfast"],
+    ),
+    (
+        "lycanrocmidnight",
+        &["Rock"],
+        &["Keen Eye", "Vital Spirit", "No Guard"],
+    ),
+    ("lycanrocdusk", &["Rock"], &["Tough Claws"]),
+    ("rotom", &["Electric", "Ghost"], &["Levitate"]),
+    ("rotomheat", &["Electric", "Fire"], &["Levitate"]),
+    ("rotomwash", &["Electric", "Water"], &["Levitate"]),
+    ("rotomfrost", &["Electric", "Ice"], &["Levitate"]),
+    ("rotomfan", &["Electric", "Flying"], &["Levitate"]),
+    ("rotommow", &["Electric", "Grass"], &["Levitate"]),
+];
+
+/// Catches a forme read from the wrong stats index, which shows up as the types or
+/// abilities of a neighbouring forme
+fn check_known_formes(dex_map: &IndexMap<String, PokemonJs>) {
+    for (id, types, abilities) in KNOWN_FORMES {
+        let Some(poke) = dex_map.get(*id) else {
+            warn!("{id}: missing from the pokedex");
+            continue;
+        };
+        if poke.types != *types {
+            warn!("{id}: types {:?} instead of {types:?}", poke.types);
+        }
+        if !abilities
+            .iter()
+            .all(|a| poke.abilities.values().any(|b| b == a))
+        {
+            warn!(
+                "{id}: abilities {:?} instead of {abilities:?}",
+                poke.abilities
+            );
+        }
+    }
+}
+
+pub fn load_pokedex(path: &Path) -> Result<IndexMap<String, PokemonJs>> {
+    Ok(serde_json::from_reader(BufReader::new(File::open(path)?))?)
+}
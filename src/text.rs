@@ -1,4 +1,7 @@
+use std::io::Cursor;
+
 use binrw::BinRead;
+use color_eyre::{eyre::bail, Result};
 
 #[derive(BinRead, Debug)]
 struct TextFileHeader {
@@ -23,27 +26,90 @@ struct EncryptedLine {
 }
 
 impl EncryptedLine {
-    fn into_string(self, mut key: u16) -> String {
-        return self
+    /// The line up to its terminator, and whether one was found
+    fn decode(&self, mut key: u16, decoder: &TextDecoder) -> (String, bool) {
+        let mut terminated = false;
+        let line = self
             .data
             .iter()
             .map_while(|u| {
                 let c = std::char::from_u32((*u ^ key) as u32).unwrap_or(' ');
-                key = key << 3 | key >> 13;
+                key = key.rotate_left(3);
                 match c {
-                    '\0' => None,
-                    '\u{E08E}' => Some('M'),
-                    '\u{E08F}' => Some('F'),
-                    'é' => Some('e'),
-                    c => Some(c)
+                    '\0' => {
+                        terminated = true;
+                        None
+                    }
+                    c => Some(decoder.substitute(c)),
                 }
             })
             .collect();
+        (line, terminated)
     }
 }
 
-const KEY_BASE: u16 = 0x7c89;
-const KEY_ADVANCE: u16 = 0x2983;
+fn read_lines<R: std::io::Read + std::io::Seek>(
+    reader: &mut R,
+    endian: binrw::Endian,
+) -> binrw::BinResult<(TextFileHeader, Vec<EncryptedLine>)> {
+    let header = TextFileHeader::read_options(reader, endian, ())?;
+    let mut lines = Vec::new();
+    reader.seek(std::io::SeekFrom::Start(
+        header.section_data_offset as u64 + 4,
+    ))?;
+    for _ in 0..header.line_count {
+        let line_info = LineInfo::read_options(reader, endian, ())?;
+        let pos = reader.stream_position()?;
+        reader.seek(std::io::SeekFrom::Start(
+            line_info.offset as u64 + header.section_data_offset as u64,
+        ))?;
+        lines.push(EncryptedLine::read_options(
+            reader,
+            endian,
+            (line_info.length,),
+        )?);
+        reader.seek(std::io::SeekFrom::Start(pos))?;
+    }
+    Ok((header, lines))
+}
+
+/// Keys of the XOR-rotate text encryption and the characters replaced after decoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextDecoder {
+    /// Key of the first line
+    pub key_base: u16,
+    /// Added to the key for each following line
+    pub key_advance: u16,
+    pub substitutions: &'static [(char, char)],
+}
+
+impl TextDecoder {
+    /// Keys of Sun/Moon and Ultra Sun/Ultra Moon
+    pub const GEN7: TextDecoder = TextDecoder {
+        key_base: 0x7c89,
+        key_advance: 0x2983,
+        substitutions: &[
+            ('\u{E08E}', '♂'),
+            ('\u{E08F}', '♀'),
+            ('é', 'e'),
+            // Showdown names use the straight apostrophe, Farfetch'd
+            ('’', '\''),
+        ],
+    };
+
+    fn substitute(&self, c: char) -> char {
+        self.substitutions
+            .iter()
+            .find(|(from, _)| *from == c)
+            .map_or(c, |(_, to)| *to)
+    }
+}
+
+impl Default for TextDecoder {
+    fn default() -> Self {
+        TextDecoder::GEN7
+    }
+}
 
 #[derive(Debug)]
 pub struct TextFile {
@@ -52,33 +118,68 @@ pub struct TextFile {
 }
 
 impl BinRead for TextFile {
-    type Args<'a> = ();
+    type Args<'a> = (TextDecoder,);
 
     fn read_options<R: std::io::Read + std::io::Seek>(
         reader: &mut R,
         endian: binrw::Endian,
-        _: Self::Args<'_>,
+        (decoder,): Self::Args<'_>,
     ) -> binrw::BinResult<Self> {
-        let header = TextFileHeader::read_options(reader, endian, ())?;
+        let (header, encrypted_lines) = read_lines(reader, endian)?;
         let mut lines: Vec<String> = Vec::new();
-        let mut key = KEY_BASE;
-        reader.seek(std::io::SeekFrom::Start(
-            header.section_data_offset as u64 + 4,
-        ))?;
-        for _ in 0..header.line_count {
-            let line_info = LineInfo::read_options(reader, endian, ())?;
-            let pos = reader.stream_position()?;
-            reader.seek(std::io::SeekFrom::Start(
-                line_info.offset as u64 + header.section_data_offset as u64,
-            ))?;
-            lines.push(
-                EncryptedLine::read_options(reader, endian, (line_info.length,))?
-                    .into_string(key),
-            );
-            reader.seek(std::io::SeekFrom::Start(pos))?;
-            key = key.wrapping_add(KEY_ADVANCE);
+        let mut key = decoder.key_base;
+        for line in &encrypted_lines {
+            lines.push(line.decode(key, &decoder).0);
+            key = key.wrapping_add(decoder.key_advance);
         }
 
         Ok(TextFile { _header: header, lines })
     }
 }
+
+/// Joins the lines of an in-game text box with spaces, a word hyphenated across two lines
+/// with a soft hyphen is joined back. Showdown descriptions are single-line.
+pub fn single_line(line: &str) -> String {
+    line.replace("\u{AD}\n", "")
+        .replace('\u{AD}', "")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Decoders of the keys decoding line `line` of the text file `bytes` to `expected`, with
+/// every line ending on its terminator. The first character gives the key of the line, each
+/// key advance is tried from there.
+pub fn find_keys(bytes: &[u8], line: usize, expected: &str) -> Result<Vec<TextDecoder>> {
+    let (_, lines) = read_lines(&mut Cursor::new(bytes), binrw::Endian::Little)?;
+    let Some(target) = lines.get(line) else {
+        bail!("the text file has {} lines, no line {line}", lines.len());
+    };
+    let (Some(data), Some(expected_char)) = (target.data.first(), expected.encode_utf16().next())
+    else {
+        bail!("line {line} and the expected string can't be empty");
+    };
+    let line_key = data ^ expected_char;
+    if target.decode(line_key, &TextDecoder::GEN7).0 != expected {
+        return Ok(Vec::new());
+    }
+
+    let mut found = Vec::new();
+    for key_advance in 0..=u16::MAX {
+        let decoder = TextDecoder {
+            key_base: line_key.wrapping_sub(key_advance.wrapping_mul(line as u16)),
+            key_advance,
+            ..TextDecoder::GEN7
+        };
+        let mut key = decoder.key_base;
+        let terminated = lines.iter().all(|line| {
+            let (_, terminated) = line.decode(key, &decoder);
+            key = key.wrapping_add(key_advance);
+            terminated
+        });
+        if terminated {
+            found.push(decoder);
+        }
+    }
+    Ok(found)
+}
@@ -0,0 +1,294 @@
+use std::{fs::File, io::BufReader, path::Path};
+
+use binrw::BinRead;
+use color_eyre::{eyre::bail, Result};
+use indexmap::{IndexMap, IndexSet};
+
+use crate::{
+    garc::{self, GarcFile},
+    garc_files, is_output_file, load_abilities, load_moves, load_pokedex,
+    moves::{is_known_target, read_move_stats, MoveStats},
+    options::Options,
+    output_file_name,
+    text::TextFile,
+    to_id,
+};
+
+fn load_reference(path: &Path) -> Result<IndexMap<String, serde_json::Value>> {
+    Ok(serde_json::from_reader(BufReader::new(File::open(path)?))?)
+}
+
+fn report_id_mismatches<'a>(
+    kind: &str,
+    generated: impl Iterator<Item = &'a String>,
+    reference: &IndexMap<String, serde_json::Value>,
+) {
+    let generated: IndexSet<&String> = generated.collect();
+    let missing: Vec<_> = generated
+        .iter()
+        .filter(|id| !reference.contains_key(**id))
+        .collect();
+    let extra: Vec<_> = reference
+        .keys()
+        .filter(|id| !generated.contains(id))
+        .collect();
+
+    println!(
+        "{kind}: {} not in reference, {} not generated",
+        missing.len(),
+        extra.len()
+    );
+    for id in missing {
+        println!("  + {id}");
+    }
+    for id in extra {
+        println!("  - {id}");
+    }
+}
+
+/// Compares the ids of the generated files in `out_path` with a Showdown data directory
+pub fn check_ids_against(out_path: &Path, reference_path: &Path) -> Result<()> {
+    let reference = reference_path.join("pokedex.json");
+    if reference.exists() {
+        let generated = load_pokedex(&out_path.join("pokedex.json"))?;
+        report_id_mismatches("pokedex", generated.keys(), &load_reference(&reference)?);
+    }
+
+    let reference = reference_path.join("moves.json");
+    if reference.exists() {
+        let generated = load_moves(&out_path.join("moves.json"))?;
+        report_id_mismatches("moves", generated.keys(), &load_reference(&reference)?);
+    }
+
+    let reference = reference_path.join("abilities.json");
+    if reference.exists() {
+        let generated = load_abilities(&out_path.join("abilities.json"))?;
+        report_id_mismatches("abilities", generated.keys(), &load_reference(&reference)?);
+    }
+
+    Ok(())
+}
+
+/// Move ids of a learnset in either the list or the Showdown format
+fn learnset_move_ids(learnset: &serde_json::Value) -> Vec<&str> {
+    match learnset {
+        serde_json::Value::Array(entries) => entries
+            .iter()
+            .filter_map(|entry| entry.get("move")?.as_str())
+            .collect(),
+        learnset => learnset
+            .get("learnset")
+            .and_then(|moves| moves.as_object())
+            .map(|moves| moves.keys().map(String::as_str).collect())
+            .unwrap_or_default(),
+    }
+}
+
+/// Learnset moves missing from the generated moves, as "species: move", which means
+/// `to_id` gave the move a different id in each
+pub fn missing_learnset_moves(out_path: &Path, options: &Options) -> Result<Vec<String>> {
+    let learnsets_file = output_file_name("learnsets", options);
+    let moves_file = output_file_name("moves", options);
+    if !learnsets_file.ends_with(".json") || !moves_file.ends_with(".json") {
+        bail!("learnset moves can only be checked in the json format");
+    }
+    let learnsets = load_reference(&out_path.join(learnsets_file))?;
+    let moves = load_reference(&out_path.join(moves_file))?;
+
+    let mut missing = Vec::new();
+    for (species, learnset) in &learnsets {
+        let move_ids: IndexSet<&str> = learnset_move_ids(learnset).into_iter().collect();
+        for move_id in move_ids {
+            if !moves.contains_key(move_id) {
+                missing.push(format!("{species}: {move_id}"));
+            }
+        }
+    }
+    Ok(missing)
+}
+
+/// Damaging moves Showdown gives no base power, their damage is computed in battle
+const VARIABLE_POWER_MOVES: &[&str] = &[
+    // weight or speed based
+    "lowkick",
+    "grassknot",
+    "heavyslam",
+    "heatcrash",
+    "electroball",
+    "gyroball",
+    // HP, happiness or item based
+    "flail",
+    "reversal",
+    "crushgrip",
+    "wringout",
+    "return",
+    "frustration",
+    "punishment",
+    "trumpcard",
+    "naturalgift",
+    "fling",
+    "spitup",
+    "magnitude",
+    "present",
+    "beatup",
+    // fixed damage
+    "seismictoss",
+    "nightshade",
+    "sonicboom",
+    "dragonrage",
+    "psywave",
+    "superfang",
+    "endeavor",
+    "finalgambit",
+    "naturesmadness",
+    "guardianofalola",
+    // returned damage
+    "counter",
+    "mirrorcoat",
+    "metalburst",
+    "bide",
+    // OHKO
+    "fissure",
+    "horndrill",
+    "guillotine",
+    "sheercold",
+];
+
+/// Moves likely mis-decoded, by kind of anomaly, for manual review. Runs on the ROM
+/// data, as the moves file normalizes status power and unknown targets away.
+pub fn suspicious_moves(
+    moves: &[MoveStats],
+    move_names: &[String],
+) -> Vec<(&'static str, Vec<String>)> {
+    let find = |suspect: &dyn Fn(&str, &MoveStats) -> bool| -> Vec<String> {
+        moves
+            .iter()
+            .zip(move_names)
+            .skip(1)
+            .map(|(mv, name)| (to_id(name.clone()), mv))
+            .filter(|(id, mv)| suspect(id, mv))
+            .map(|(id, _)| id)
+            .collect()
+    };
+    vec![
+        (
+            "damaging with no base power",
+            find(&|id, mv| {
+                matches!(mv.category, 1 | 2) && mv.power == 0 && !VARIABLE_POWER_MOVES.contains(&id)
+            }),
+        ),
+        (
+            "status with a base power",
+            find(&|_, mv| !matches!(mv.category, 1 | 2) && mv.power != 0),
+        ),
+        ("accuracy 0", find(&|_, mv| mv.accuracy == 0)),
+        ("unknown target", find(&|_, mv| !is_known_target(mv.target))),
+    ]
+}
+
+/// Suspicious moves of the ROM's move GARC
+pub fn check_moves(rom_path: &Path, options: &Options) -> Result<Vec<(&'static str, Vec<String>)>> {
+    let text_path = rom_path.join(garc_files::BASE_PATH).join(garc_files::TEXT);
+    let decoder = options.rom_type.text_decoder();
+    let text_files = garc::read_files_with::<TextFile>(&garc::open(&text_path)?, |_| (decoder,))?;
+    let move_names = &text_files[options.text_ids().move_names].lines;
+    Ok(suspicious_moves(&read_move_stats(rom_path)?, move_names))
+}
+
+/// Refuses an output directory holding files the tool doesn't write, in case it was
+/// mistyped. `--force` skips the check.
+pub fn check_out_dir(out_path: &Path) -> Result<()> {
+    if !out_path.exists() {
+        return Ok(());
+    }
+    let mut foreign = Vec::new();
+    for entry in std::fs::read_dir(out_path)? {
+        let file_name = entry?.file_name().to_string_lossy().into_owned();
+        if !is_output_file(&file_name) {
+            foreign.push(file_name);
+        }
+    }
+    if !foreign.is_empty() {
+        foreign.sort();
+        bail!(
+            "{} contains files this tool didn't write ({}), pass --force to write there anyway",
+            out_path.display(),
+            foreign.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Showdown `damageTaken` codes
+const WEAK: u8 = 1;
+const RESIST: u8 = 2;
+const IMMUNE: u8 = 3;
+
+/// Attacking type, defending type and the defender's `damageTaken` code. Most are one-way
+/// so a chart with attackers and defenders swapped fails them.
+const CANONICAL_MATCHUPS: &[(&str, &str, u8)] = &[
+    ("Water", "Fire", WEAK),
+    ("Fire", "Water", RESIST),
+    ("Grass", "Water", WEAK),
+    ("Fire", "Grass", WEAK),
+    ("Electric", "Ground", IMMUNE),
+    ("Electric", "Flying", WEAK),
+    ("Ground", "Flying", IMMUNE),
+    ("Normal", "Ghost", IMMUNE),
+    ("Fighting", "Ghost", IMMUNE),
+    ("Ghost", "Normal", IMMUNE),
+    ("Fighting", "Normal", WEAK),
+    ("Psychic", "Dark", IMMUNE),
+    ("Dragon", "Fairy", IMMUNE),
+    ("Poison", "Steel", IMMUNE),
+    ("Ice", "Dragon", WEAK),
+    ("Steel", "Steel", RESIST),
+];
+
+#[allow(non_snake_case)]
+#[derive(serde::Deserialize)]
+struct TypeChartEntry {
+    damageTaken: IndexMap<String, u8>,
+}
+
+/// Checks a Showdown-format typechart.json against known matchups, to catch a transposed
+/// attacker/defender matrix
+pub fn check_typechart(path: &Path) -> Result<()> {
+    let chart: IndexMap<String, TypeChartEntry> =
+        serde_json::from_reader(BufReader::new(File::open(path)?))?;
+    let chart: IndexMap<String, TypeChartEntry> = chart
+        .into_iter()
+        .map(|(id, entry)| (to_id(id), entry))
+        .collect();
+
+    let mut mismatches = Vec::new();
+    for (attacker, defender, expected) in CANONICAL_MATCHUPS {
+        let found = chart
+            .get(&to_id((*defender).to_owned()))
+            .and_then(|entry| entry.damageTaken.get(*attacker))
+            .copied();
+        if found != Some(*expected) {
+            mismatches.push(format!(
+                "{attacker} -> {defender}: expected {expected}, found {found:?}"
+            ));
+        }
+    }
+    if !mismatches.is_empty() {
+        bail!("type chart mismatches:\n{}", mismatches.join("\n"));
+    }
+    println!("type chart: {} matchups ok", CANONICAL_MATCHUPS.len());
+    Ok(())
+}
+
+/// Prints the file count of every known GARC without decoding their contents
+pub fn print_garc_counts(rom_path: &Path) -> Result<()> {
+    for (name, garc_path) in garc_files::ALL {
+        let path = rom_path.join(garc_files::BASE_PATH).join(garc_path);
+        match File::open(&path).map(|mut f| GarcFile::read_le(&mut f)) {
+            Ok(Ok(garc)) => println!("{name:<14} {garc_path}  {}", garc.file_count()),
+            Ok(Err(e)) => println!("{name:<14} {garc_path}  invalid GARC: {e}"),
+            Err(e) => println!("{name:<14} {garc_path}  {e}"),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,58 @@
+//! Checks the ability entries built from the text GARC
+
+mod common;
+
+use common::Romfs;
+use serde_json::json;
+use usum_extractor::options::Options;
+
+#[test]
+fn shortens_levitate_desc() {
+    let mut romfs = Romfs::default();
+    romfs.add_ability_with_desc(
+        "Levitate",
+        "By floating in the air, the Pokemon receives full immunity to all Ground-type moves. \
+         It still lands for Gravity.",
+    );
+    let abilities = romfs.write().abilities(Options::default());
+
+    let levitate = &abilities["levitate"];
+    assert_eq!(
+        levitate["shortDesc"],
+        "By floating in the air, the Pokemon receives full immunity to all Ground-type moves."
+    );
+    assert_ne!(levitate["shortDesc"], levitate["desc"]);
+    assert_eq!(levitate["isNonstandard"], json!(null));
+}
+
+#[test]
+fn marks_future_abilities() {
+    let mut romfs = Romfs::default();
+    romfs.add_ability("Levitate");
+    // Beast Boost is the last Gen 7 ability
+    romfs.abilities.resize(233, Default::default());
+    romfs.add_ability("Beast Boost");
+    romfs.add_ability("Intrepid Sword");
+    let abilities = romfs.write().abilities(Options::default());
+
+    assert_eq!(abilities["beastboost"]["isNonstandard"], json!(null));
+    assert_eq!(abilities["intrepidsword"]["isNonstandard"], "Future");
+}
+
+/// The forme-changing abilities can't be suppressed, Zen Mode included
+#[test]
+fn flags_zen_mode_cantsuppress() {
+    let mut romfs = Romfs::default();
+    romfs.add_ability("Zen Mode");
+    romfs.add_ability("Stance Change");
+    let abilities = romfs.write().abilities(Options {
+        ability_flags: true,
+        ..Default::default()
+    });
+
+    assert_eq!(abilities["zenmode"]["flags"], json!({"cantsuppress": 1}));
+    assert_eq!(
+        abilities["stancechange"]["flags"],
+        json!({"cantsuppress": 1})
+    );
+}
@@ -0,0 +1,473 @@
+//! Builders for the ROM files the extractor reads, to run it on a synthetic romfs
+
+// Each test crate uses its own part of the builders
+#![allow(dead_code)]
+
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use indexmap::IndexMap;
+use serde_json::Value;
+use usum_extractor::{
+    build_abilities, garc, garc_files,
+    learnset::{self, Learnset},
+    moves,
+    options::Options,
+    pokemon,
+    text::TextFile,
+    text_ids, RomData,
+};
+
+fn push_u16(out: &mut Vec<u8>, value: u16) {
+    out.extend(value.to_le_bytes());
+}
+
+fn push_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend(value.to_le_bytes());
+}
+
+/// A GARC holding each of `files` as the subfile 0 of an entry
+pub fn garc(files: &[Vec<u8>]) -> Vec<u8> {
+    let count = files.len() as u32;
+
+    let mut fato = b"OTAF".to_vec();
+    push_u32(&mut fato, 12 + 4 * count);
+    push_u16(&mut fato, count as u16);
+    push_u16(&mut fato, 0xffff);
+    for index in 0..count {
+        push_u32(&mut fato, index * 16);
+    }
+
+    let mut fatb = b"BTAF".to_vec();
+    push_u32(&mut fatb, 12 + 16 * count);
+    push_u32(&mut fatb, count);
+    let mut data: Vec<u8> = Vec::new();
+    for file in files {
+        push_u32(&mut fatb, 1);
+        push_u32(&mut fatb, data.len() as u32);
+        push_u32(&mut fatb, (data.len() + file.len()) as u32);
+        push_u32(&mut fatb, file.len() as u32);
+        data.extend(file);
+    }
+
+    let mut fimb = b"BMIF".to_vec();
+    push_u32(&mut fimb, 12);
+    push_u32(&mut fimb, data.len() as u32);
+    fimb.extend(data);
+
+    let mut out = b"CRAG".to_vec();
+    push_u32(&mut out, 0x18);
+    push_u16(&mut out, 0xfeff);
+    push_u16(&mut out, 0x0400);
+    push_u32(&mut out, 4);
+    push_u32(&mut out, (0x18 + fato.len() + fatb.len()) as u32);
+    push_u32(
+        &mut out,
+        (0x18 + fato.len() + fatb.len() + fimb.len()) as u32,
+    );
+    out.extend(fato);
+    out.extend(fatb);
+    out.extend(fimb);
+    out
+}
+
+/// A text file with `lines`, encrypted like the game's
+pub fn text_file(lines: &[&str]) -> Vec<u8> {
+    text_file_with_keys(lines, 0x7c89, 0x2983)
+}
+
+/// A text file with `lines`, encrypted with the key `key_base` advanced by `key_advance`
+/// for each line
+pub fn text_file_with_keys(lines: &[&str], key_base: u16, key_advance: u16) -> Vec<u8> {
+    const HEADER_LEN: u32 = 0x14;
+    let table_len = 4 + 8 * lines.len();
+    let mut table = Vec::new();
+    let mut data = Vec::new();
+    let mut key = key_base;
+    for line in lines {
+        let chars: Vec<u16> = line.encode_utf16().chain([0]).collect();
+        push_u32(&mut table, (table_len + data.len()) as u32);
+        push_u32(&mut table, chars.len() as u32);
+        let mut char_key = key;
+        for c in chars {
+            push_u16(&mut data, c ^ char_key);
+            char_key = char_key.rotate_left(3);
+        }
+        key = key.wrapping_add(key_advance);
+    }
+    let section_len = (table_len + data.len()) as u32;
+
+    let mut out = Vec::new();
+    push_u16(&mut out, 1);
+    push_u16(&mut out, lines.len() as u16);
+    push_u32(&mut out, HEADER_LEN + section_len);
+    push_u32(&mut out, 0);
+    push_u32(&mut out, HEADER_LEN);
+    push_u32(&mut out, section_len);
+    push_u32(&mut out, section_len);
+    out.extend(table);
+    out.extend(data);
+    out
+}
+
+/// The personal data fields the tests set, the others are left at 0
+#[derive(Clone, Default)]
+pub struct Personal {
+    pub stats: [u8; 6],
+    pub types: (u8, u8),
+    pub items: [u16; 3],
+    pub gender: u8,
+    pub egg_groups: [u8; 2],
+    pub abilities: [u8; 3],
+    pub form_stats_id: u16,
+    pub form_count: u8,
+    pub sprite_bits: u8,
+    pub weight: u16,
+    pub tm_bits: [u8; 0x10],
+    pub beach_bits: [u8; 0xa],
+}
+
+impl Personal {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = self.stats.to_vec();
+        out.extend([self.types.0, self.types.1, 0, 0]);
+        push_u16(&mut out, 0); // ev_yield
+        for item in self.items {
+            push_u16(&mut out, item);
+        }
+        out.extend([self.gender, 0, 0, 0]);
+        out.extend(self.egg_groups);
+        out.extend(self.abilities);
+        out.push(0); // escape_rate
+        push_u16(&mut out, self.form_stats_id);
+        push_u16(&mut out, 0); // form_sprite
+        out.extend([self.form_count.max(1), self.sprite_bits]);
+        push_u16(&mut out, 0); // base_exp
+        push_u16(&mut out, 0); // height
+        push_u16(&mut out, self.weight);
+        out.extend(self.tm_bits);
+        out.extend([0; 0x4]); // tutor_bits
+        out.extend(self.beach_bits);
+        out
+    }
+}
+
+/// The move data fields the tests set, the others are left at 0
+#[derive(Clone, Default)]
+pub struct Move {
+    pub move_type: u8,
+    pub quality: u8,
+    pub category: u8,
+    pub power: u8,
+    pub accuracy: u8,
+    pub pp: u8,
+    pub priority: i8,
+    pub hit_min_max: u8,
+    pub inflict: u16,
+    pub inflict_percent: u8,
+    pub recoil_absorption: i8,
+    pub heal: u8,
+    pub target: u8,
+    pub stat: [u8; 3],
+    pub stat_stage: [i8; 3],
+    pub stat_percent: [u8; 3],
+    pub z_move: u16,
+    pub z_power: u8,
+    pub flags: u32,
+}
+
+impl Move {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut out = vec![
+            self.move_type,
+            self.quality,
+            self.category,
+            self.power,
+            self.accuracy,
+            self.pp,
+            self.priority as u8,
+            self.hit_min_max,
+        ];
+        push_u16(&mut out, self.inflict);
+        out.extend([self.inflict_percent, 0]); // inflict_duration
+        out.extend([0; 4]); // turn_min to flinch
+        push_u16(&mut out, 0); // effect_sequence
+        out.extend([self.recoil_absorption as u8, self.heal]);
+        out.push(self.target);
+        out.extend(self.stat);
+        out.extend(self.stat_stage.map(|stage| stage as u8));
+        out.extend(self.stat_percent);
+        push_u16(&mut out, self.z_move);
+        out.extend([self.z_power, 0, 0, 0]); // z_effect, refresh_type, refresh_percent
+        push_u32(&mut out, self.flags);
+        out
+    }
+}
+
+/// The "WD" container the move GARC keeps every move in
+pub fn move_container(moves: &[Move]) -> Vec<u8> {
+    let moves: Vec<Vec<u8>> = moves.iter().map(Move::to_bytes).collect();
+    let mut out = b"WD".to_vec();
+    push_u16(&mut out, moves.len() as u16);
+    let mut offset = 4 + 4 * moves.len();
+    for mv in &moves {
+        push_u32(&mut out, offset as u32);
+        offset += mv.len();
+    }
+    out.extend(moves.concat());
+    out
+}
+
+/// Evolutions of a species as (method, argument, species, level), up to 8
+pub fn evolutions(evos: &[(u16, u16, u16, u8)]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for (method, argument, species, level) in evos {
+        push_u16(&mut out, *method);
+        push_u16(&mut out, *argument);
+        push_u16(&mut out, *species);
+        out.extend([0, *level]);
+    }
+    out.resize(8 * 8, 0);
+    out
+}
+
+/// Level up moves as (move, level)
+pub fn level_up_moves(moves: &[(i16, i16)]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for (move_id, level) in moves.iter().chain([&(-1, -1)]) {
+        out.extend(move_id.to_le_bytes());
+        out.extend(level.to_le_bytes());
+    }
+    out
+}
+
+pub fn write_garc(rom_path: &Path, garc_path: &str, files: &[Vec<u8>]) {
+    let path = rom_path.join("romfs/a").join(garc_path);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, garc(files)).unwrap();
+}
+
+/// Index of the first forme's personal data, after the last species
+pub const SPECIES_COUNT: usize = 808;
+
+pub const TYPE_NAMES: [&str; 18] = [
+    "Normal", "Fighting", "Flying", "Poison", "Ground", "Rock", "Bug", "Ghost", "Steel", "Fire",
+    "Water", "Grass", "Electric", "Psychic", "Ice", "Dragon", "Dark", "Fairy",
+];
+
+/// The files of a species or forme, indexed like its personal data
+#[derive(Clone, Default)]
+pub struct Species {
+    pub personal: Personal,
+    pub evolutions: Vec<(u16, u16, u16, u8)>,
+    pub level_up: Vec<(i16, i16)>,
+    /// Mega evolutions as (forme, method, argument)
+    pub mega_evos: Vec<(u16, u16, u16)>,
+}
+
+/// A whole romfs, the species, moves, abilities and items are added with their names
+pub struct Romfs {
+    pub species_names: Vec<String>,
+    pub species: Vec<Species>,
+    pub moves: Vec<(String, Move, String)>,
+    pub abilities: Vec<(String, String)>,
+    pub items: Vec<String>,
+}
+
+impl Default for Romfs {
+    fn default() -> Romfs {
+        Romfs {
+            species_names: vec!["Egg".to_owned()],
+            species: vec![Species::default()],
+            moves: vec![("-".to_owned(), Move::default(), String::new())],
+            abilities: vec![("-".to_owned(), String::new())],
+            items: vec!["None".to_owned()],
+        }
+    }
+}
+
+impl Romfs {
+    /// Adds the species `name` at `index`, the species before it are left blank
+    pub fn add_species(&mut self, index: usize, name: &str, personal: Personal) -> &mut Species {
+        if self.species_names.len() <= index {
+            self.species_names.resize(index + 1, String::new());
+        }
+        if self.species.len() <= index {
+            self.species.resize(index + 1, Species::default());
+        }
+        self.species_names[index] = name.to_owned();
+        self.species[index].personal = personal;
+        &mut self.species[index]
+    }
+
+    /// Appends the formes of the species at `base` after the last species, returns the index
+    /// of the first one
+    pub fn add_formes(&mut self, base: usize, formes: Vec<Personal>) -> usize {
+        if self.species.len() < SPECIES_COUNT {
+            self.species.resize(SPECIES_COUNT, Species::default());
+            self.species_names.resize(SPECIES_COUNT, String::new());
+        }
+        let first = self.species.len();
+        let personal = &mut self.species[base].personal;
+        personal.form_stats_id = first as u16;
+        personal.form_count = formes.len() as u8 + 1;
+        self.species
+            .extend(formes.into_iter().map(|personal| Species {
+                personal,
+                ..Default::default()
+            }));
+        first
+    }
+
+    /// Adds the move `name` as the move number `num`, the moves before it are left blank
+    pub fn set_move(&mut self, num: usize, name: &str, mv: Move) -> i16 {
+        self.moves
+            .resize(num, (String::new(), Move::default(), String::new()));
+        self.add_move(name, mv)
+    }
+
+    pub fn add_move(&mut self, name: &str, mv: Move) -> i16 {
+        self.add_move_with_desc(name, mv, "")
+    }
+
+    pub fn add_move_with_desc(&mut self, name: &str, mv: Move, desc: &str) -> i16 {
+        self.moves.push((name.to_owned(), mv, desc.to_owned()));
+        self.moves.len() as i16 - 1
+    }
+
+    pub fn add_ability(&mut self, name: &str) -> u8 {
+        self.add_ability_with_desc(name, "")
+    }
+
+    pub fn add_ability_with_desc(&mut self, name: &str, desc: &str) -> u8 {
+        self.abilities.push((name.to_owned(), desc.to_owned()));
+        self.abilities.len() as u8 - 1
+    }
+
+    pub fn add_item(&mut self, name: &str) -> u16 {
+        self.items.push(name.to_owned());
+        self.items.len() as u16 - 1
+    }
+
+    /// The text GARC holding the names and descriptions
+    pub fn text_garc(&self) -> Vec<Vec<u8>> {
+        let text = |lines: Vec<&str>| text_file(&lines);
+        let mut texts = vec![text_file(&[]); text_ids::MOVE_NAMES + 1];
+        texts[text_ids::SPECIES_NAMES] =
+            text(self.species_names.iter().map(String::as_str).collect());
+        texts[text_ids::ITEM_NAMES] = text(self.items.iter().map(String::as_str).collect());
+        texts[text_ids::ABILITY_NAMES] =
+            text(self.abilities.iter().map(|a| a.0.as_str()).collect());
+        texts[text_ids::ABILITY_DESCS] =
+            text(self.abilities.iter().map(|a| a.1.as_str()).collect());
+        texts[text_ids::MOVE_NAMES] = text(self.moves.iter().map(|m| m.0.as_str()).collect());
+        texts[text_ids::MOVE_DESCS] = text(self.moves.iter().map(|m| m.2.as_str()).collect());
+        texts[text_ids::TYPE_NAMES] = text_file(&TYPE_NAMES);
+        texts
+    }
+
+    /// Writes every GARC to a new temporary directory
+    pub fn write(&self) -> TempRom {
+        let rom = TempRom::new();
+        let path = &rom.0;
+        write_garc(path, garc_files::TEXT, &self.text_garc());
+        let species = |file: fn(&Species) -> Vec<u8>| -> Vec<Vec<u8>> {
+            self.species.iter().map(file).collect()
+        };
+        write_garc(
+            path,
+            garc_files::POKEMON_STATS,
+            &species(|s| s.personal.to_bytes()),
+        );
+        write_garc(
+            path,
+            garc_files::EVOLUTIONS,
+            &species(|s| evolutions(&s.evolutions)),
+        );
+        write_garc(
+            path,
+            garc_files::LVL_UP_MOVES,
+            &species(|s| level_up_moves(&s.level_up)),
+        );
+        write_garc(
+            path,
+            garc_files::MEGA_EVOS,
+            &species(|s| mega_evos(&s.mega_evos)),
+        );
+        let moves: Vec<Move> = self.moves.iter().map(|m| m.1.clone()).collect();
+        write_garc(path, garc_files::MOVE, &[move_container(&moves)]);
+        rom
+    }
+}
+
+/// Mega evolutions as (forme, method, argument)
+pub fn mega_evos(megas: &[(u16, u16, u16)]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for (forme, method, argument) in megas {
+        push_u16(&mut out, *forme);
+        push_u16(&mut out, *method);
+        push_u16(&mut out, *argument);
+        push_u16(&mut out, 0);
+    }
+    out
+}
+
+/// A romfs written to a temporary directory, removed when dropped
+pub struct TempRom(pub PathBuf);
+
+impl TempRom {
+    pub fn new() -> TempRom {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let count = COUNT.fetch_add(1, Ordering::Relaxed);
+        let name = format!("usum-test-{}-{count}", std::process::id());
+        TempRom(std::env::temp_dir().join(name))
+    }
+
+    /// `options` reading this romfs
+    pub fn options(&self, options: Options) -> Options {
+        Options {
+            rom_path: self.0.clone(),
+            ..options
+        }
+    }
+
+    pub fn text_files(&self) -> Vec<TextFile> {
+        let text_path = self.0.join(garc_files::BASE_PATH).join(garc_files::TEXT);
+        garc::read_files(&garc::open(&text_path).unwrap()).unwrap()
+    }
+
+    pub fn pokedex(&self, options: Options) -> Value {
+        let options = self.options(options);
+        let rom = RomData::new(&self.0);
+        let (pokedex, _) = pokemon::build_pokedex(&rom, &self.text_files(), &options).unwrap();
+        serde_json::to_value(pokedex).unwrap()
+    }
+
+    pub fn moves(&self, options: Options) -> Value {
+        let options = self.options(options);
+        let rom = RomData::new(&self.0);
+        let moves = moves::build_moves(&rom, &self.text_files(), &options).unwrap();
+        serde_json::to_value(moves).unwrap()
+    }
+
+    pub fn abilities(&self, options: Options) -> Value {
+        let options = self.options(options);
+        serde_json::to_value(build_abilities(&self.text_files(), &options).unwrap()).unwrap()
+    }
+
+    pub fn learnsets(&self, options: Options) -> IndexMap<String, Learnset> {
+        let options = self.options(options);
+        let text_files = self.text_files();
+        let rom = RomData::new(&self.0);
+        let names = pokemon::build_name_map(rom.pokemons().unwrap(), &text_files, &options);
+        learnset::build_learnsets(&rom, &text_files, &names, &options).unwrap()
+    }
+}
+
+impl Drop for TempRom {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,74 @@
+//! Starting tiers of the formats-data classifier
+
+use indexmap::IndexMap;
+use serde_json::json;
+use usum_extractor::{
+    formats::{build_formats_data, DEFAULT_TIER},
+    options::Options,
+    pokemon::PokemonJs,
+};
+
+fn entry(name: &str, extra: serde_json::Value) -> PokemonJs {
+    let mut entry = json!({
+        "num": 1,
+        "name": name,
+        "types": ["Normal"],
+        "baseStats": {"hp": 1, "atk": 1, "def": 1, "spe": 1, "spa": 1, "spd": 1},
+        "abilities": {"0": "Pressure"},
+        "weightkg": 1.0,
+        "eggGroups": ["Undiscovered"],
+    });
+    entry
+        .as_object_mut()
+        .unwrap()
+        .extend(extra.as_object().unwrap().clone());
+    serde_json::from_value(entry).unwrap()
+}
+
+fn dex_map() -> IndexMap<String, PokemonJs> {
+    IndexMap::from([
+        ("mewtwo".to_owned(), entry("Mewtwo", json!({}))),
+        (
+            "mewtwomegax".to_owned(),
+            entry(
+                "Mewtwo-Mega-X",
+                json!({"baseSpecies": "Mewtwo", "forme": "Mega-X"}),
+            ),
+        ),
+        ("pikachu".to_owned(), entry("Pikachu", json!({}))),
+        (
+            "blacephalon".to_owned(),
+            entry("Blacephalon", json!({"isNonstandard": "Future"})),
+        ),
+        (
+            "wishiwashischool".to_owned(),
+            entry(
+                "Wishiwashi-School",
+                json!({"baseSpecies": "Wishiwashi", "forme": "School", "battleOnly": "Wishiwashi"}),
+            ),
+        ),
+        ("wishiwashi".to_owned(), entry("Wishiwashi", json!({}))),
+    ])
+}
+
+#[test]
+fn classifies_tiers() {
+    let formats_data = build_formats_data(&dex_map(), &Options::default());
+    assert_eq!(formats_data["mewtwo"].tier, "Uber");
+    assert_eq!(formats_data["mewtwomegax"].tier, "Uber");
+    assert_eq!(formats_data["pikachu"].tier, DEFAULT_TIER);
+    assert_eq!(formats_data["blacephalon"].tier, "Illegal");
+    assert_eq!(formats_data["wishiwashischool"].tier, DEFAULT_TIER);
+}
+
+#[test]
+fn uses_the_default_tier() {
+    let options = Options {
+        default_tier: Some("PU".to_owned()),
+        ..Default::default()
+    };
+    let formats_data = build_formats_data(&dex_map(), &options);
+    assert_eq!(formats_data["mewtwo"].tier, "Uber");
+    assert_eq!(formats_data["pikachu"].tier, "PU");
+    assert_eq!(formats_data["wishiwashischool"].tier, "PU");
+}
@@ -0,0 +1,65 @@
+//! Checks the GARC reader on crafted files
+
+mod common;
+
+use common::{garc, TempRom};
+use usum_extractor::garc as garc_reader;
+
+/// Offset of the first FATB entry's end in a GARC from `common::garc` with `count` files
+fn first_end_offset(count: usize) -> usize {
+    let fato_len = 12 + 4 * count;
+    0x18 + fato_len + 12 + 8
+}
+
+/// Writes `bytes` as a GARC file and opens it
+fn open(bytes: &[u8]) -> garc_reader::GarcFile {
+    let rom = TempRom::new();
+    std::fs::create_dir_all(&rom.0).unwrap();
+    let path = rom.0.join("garc");
+    std::fs::write(&path, bytes).unwrap();
+    garc_reader::open(&path).unwrap()
+}
+
+#[test]
+fn reads_subfiles() {
+    let garc = open(&garc(&[vec![1, 2], vec![3]]));
+    assert_eq!(garc.file_count(), 2);
+    assert_eq!(garc_reader::file_bytes(&garc, 1).unwrap(), [3]);
+    assert_eq!(garc_reader::read_file::<u8>(0, 0, &garc), Some(1));
+    assert_eq!(garc_reader::read_file::<u8>(0, 1, &garc), None);
+    assert_eq!(garc_reader::read_files::<u8>(&garc).unwrap(), [1, 3]);
+}
+
+#[test]
+fn rejects_out_of_range_entries() {
+    let mut bytes = garc(&[vec![1, 2], vec![3]]);
+    let end = first_end_offset(2);
+    bytes[end..end + 4].copy_from_slice(&0x100u32.to_le_bytes());
+    let garc = open(&bytes);
+
+    let error = garc_reader::read_files::<u8>(&garc).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "entry 0 subfile 0 has end<start or exceeds data length \
+         (start 0x0, end 0x100, data length 0x3)"
+    );
+    assert!(garc_reader::file_bytes(&garc, 0).is_err());
+    assert_eq!(garc_reader::read_file::<u8>(0, 0, &garc), None);
+}
+
+#[test]
+fn rejects_reversed_entries() {
+    let mut bytes = garc(&[vec![1, 2], vec![3]]);
+    // The second entry, ending before it starts
+    let end = first_end_offset(2) + 16;
+    bytes[end..end + 4].copy_from_slice(&1u32.to_le_bytes());
+    let garc = open(&bytes);
+
+    let error = garc_reader::read_files::<u8>(&garc).unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .starts_with("entry 1 subfile 0 has end<start"),
+        "{error}"
+    );
+}
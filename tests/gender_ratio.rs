@@ -0,0 +1,27 @@
+//! Gender bytes against the ratios Showdown writes for them
+
+use usum_extractor::pokemon::gender_ratio;
+
+fn ratio(gender: u8) -> (f32, f32) {
+    let (_, ratio) = gender_ratio(gender);
+    let ratio = ratio.unwrap();
+    (ratio.M, ratio.F)
+}
+
+#[test]
+fn ratio_bytes() {
+    assert_eq!(ratio(31), (0.875, 0.125));
+    assert_eq!(ratio(63), (0.75, 0.25));
+    assert_eq!(ratio(127), (0.5, 0.5));
+    assert_eq!(ratio(191), (0.25, 0.75));
+    assert_eq!(ratio(225), (0.125, 0.875));
+}
+
+#[test]
+fn single_gender_bytes() {
+    for (gender, expected) in [(0, "M"), (254, "F"), (255, "N")] {
+        let (gender, ratio) = gender_ratio(gender);
+        assert_eq!(gender.as_deref(), Some(expected));
+        assert!(ratio.is_none());
+    }
+}
@@ -0,0 +1,50 @@
+//! Checks the ids given to names that aren't plain ascii words
+
+mod common;
+
+use common::{Personal, Romfs};
+use usum_extractor::{options::Options, to_id};
+
+#[test]
+fn matches_showdown_ids() {
+    for (name, id) in [
+        ("Type: Null", "typenull"),
+        ("Ho-Oh", "hooh"),
+        ("Porygon-Z", "porygonz"),
+        ("Farfetch'd", "farfetchd"),
+        ("Mr. Mime", "mrmime"),
+        ("Nidoran♀", "nidoranf"),
+        ("Nidoran♂", "nidoranm"),
+    ] {
+        assert_eq!(to_id(name.to_owned()), id, "{name}");
+    }
+}
+
+/// The names as the ROM stores them, with the private use gender symbols and accents
+#[test]
+fn gives_decoded_species_showdown_ids() {
+    let mut romfs = Romfs::default();
+    let personal = || Personal {
+        stats: [50; 6],
+        ..Default::default()
+    };
+    for (index, name) in [
+        (29, "Nidoran\u{E08F}"),
+        (32, "Nidoran\u{E08E}"),
+        (83, "Farfetch’d"),
+        (772, "Type: Null"),
+        (669, "Flabébé"),
+    ] {
+        romfs.add_species(index, name, personal());
+    }
+    let pokedex = romfs.write().pokedex(Options::default());
+
+    let ids: Vec<&String> = pokedex.as_object().unwrap().keys().collect();
+    assert_eq!(
+        ids,
+        ["nidoranf", "nidoranm", "farfetchd", "flabebe", "typenull"]
+    );
+    assert_eq!(pokedex["nidoranf"]["name"], "Nidoran♀");
+    assert_eq!(pokedex["farfetchd"]["name"], "Farfetch'd");
+    assert_eq!(pokedex["flabebe"]["name"], "Flabebe");
+}
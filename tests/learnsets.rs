@@ -0,0 +1,220 @@
+//! Checks the learnsets built from the level up moves and the personal data bits
+
+mod common;
+
+use common::{write_garc, Move, Personal, Romfs, TempRom};
+use serde_json::json;
+use usum_extractor::{
+    garc, garc_files,
+    language::Language,
+    learnset,
+    options::{LearnsetFormat, Options, RomType},
+    pokemon,
+    text::TextFile,
+    RomData,
+};
+
+const EARTHQUAKE: usize = 89;
+/// TM06 is Earthquake
+const EARTHQUAKE_TM: usize = 5;
+
+/// A species learning Earthquake by level up and TM, and a Gen 7 move by level up
+fn earthquake_romfs() -> Romfs {
+    let mut romfs = Romfs::default();
+    let earthquake = romfs.set_move(EARTHQUAKE, "Earthquake", Move::default());
+    let shore_up = romfs.set_move(659, "Shore Up", Move::default());
+    let mut tm_bits = [0; 0x10];
+    tm_bits[EARTHQUAKE_TM / 8] = 1 << (EARTHQUAKE_TM % 8);
+    let personal = Personal {
+        stats: [50; 6],
+        tm_bits,
+        ..Default::default()
+    };
+    romfs.add_species(1, "Sandygast", personal).level_up = vec![(earthquake, 1), (shore_up, 5)];
+    romfs
+}
+
+#[test]
+fn adds_lower_gen_sources() {
+    let learnsets = earthquake_romfs().write().learnsets(Options::default());
+    let sources = |from| serde_json::to_value(learnsets["sandygast"].to_showdown(7, from)).unwrap();
+
+    assert_eq!(
+        sources(None)["learnset"],
+        json!({"earthquake": ["7L1", "7M"], "shoreup": ["7L5"]})
+    );
+    assert_eq!(
+        sources(Some(5))["learnset"],
+        json!({
+            "earthquake": ["7L1", "6L1", "5L1", "7M", "6M", "5M"],
+            "shoreup": ["7L5"],
+        })
+    );
+}
+
+/// The TM moves are listed by English name, other languages still get their numbers for
+/// the earlier-gen sources. Without those the English text GARC isn't needed.
+#[test]
+fn numbers_tms_in_other_languages() {
+    let romfs = earthquake_romfs();
+    let rom = romfs.write();
+    let mut french = earthquake_romfs();
+    french.moves[EARTHQUAKE].0 = "Séisme".to_owned();
+    let french_garc = Language::French.text_garc();
+    write_garc(&rom.0, french_garc, &french.text_garc());
+
+    let text_path = rom.0.join(garc_files::BASE_PATH).join(french_garc);
+    let text_files = garc::read_files::<TextFile>(&garc::open(&text_path).unwrap()).unwrap();
+    let earthquake = |learnset_sources_from| {
+        let options = Options {
+            language: Some(Language::French),
+            learnset_sources_from,
+            ..rom.options(Options::default())
+        };
+        let rom_data = RomData::new(&rom.0);
+        let names = pokemon::build_name_map(rom_data.pokemons().unwrap(), &text_files, &options);
+        let learnsets = learnset::build_learnsets(&rom_data, &text_files, &names, &options);
+        let showdown = learnsets.unwrap()["sandygast"].to_showdown(7, learnset_sources_from);
+        serde_json::to_value(showdown).unwrap()["learnset"]["earthquake"].clone()
+    };
+
+    assert_eq!(earthquake(Some(5)), json!(["7M", "6M", "5M"]));
+    std::fs::remove_file(rom.0.join(garc_files::BASE_PATH).join(garc_files::TEXT)).unwrap();
+    assert_eq!(earthquake(None), json!(["7M"]));
+}
+
+/// A TM without a number only fails the run when the earlier-gen sources need it
+#[test]
+fn fails_on_a_tm_missing_from_the_moves() {
+    let mut romfs = earthquake_romfs();
+    romfs.moves[EARTHQUAKE].0 = "Quake".to_owned();
+    let rom = romfs.write();
+    let text_files = rom.text_files();
+    let rom_data = RomData::new(&rom.0);
+    let build = |learnset_sources_from| {
+        let options = Options {
+            learnset_sources_from,
+            ..rom.options(Options::default())
+        };
+        let names = pokemon::build_name_map(rom_data.pokemons().unwrap(), &text_files, &options);
+        learnset::build_learnsets(&rom_data, &text_files, &names, &options)
+    };
+
+    let error = build(Some(5)).err().unwrap();
+    assert_eq!(
+        error.to_string(),
+        "TM06 Earthquake isn't in the English move names"
+    );
+    let learnsets = build(None).unwrap();
+    let showdown = serde_json::to_value(learnsets["sandygast"].to_showdown(7, None)).unwrap();
+    assert_eq!(
+        showdown["learnset"],
+        json!({"quake": ["7L1"], "earthquake": ["7M"], "shoreup": ["7L5"]})
+    );
+}
+
+/// Bits are read least significant first, TM01 being the lowest bit of the first byte
+#[test]
+fn reads_tm_bits_in_order() {
+    let mut romfs = Romfs::default();
+    romfs.set_move(113, "Light Screen", Move::default());
+    romfs.set_move(349, "Dragon Dance", Move::default());
+    let mut tm_bits = [0; 0x10];
+    // TM02 Dragon Dance and TM16 Light Screen
+    tm_bits[0] = 0b0000_0010;
+    tm_bits[1] = 0b1000_0000;
+    let personal = Personal {
+        stats: [50; 6],
+        tm_bits,
+        ..Default::default()
+    };
+    romfs.add_species(1, "Bagon", personal);
+    let learnsets = romfs.write().learnsets(Options::default());
+    let showdown = serde_json::to_value(learnsets["bagon"].to_showdown(7, None)).unwrap();
+    assert_eq!(
+        showdown["learnset"],
+        json!({"dragondance": ["7M"], "lightscreen": ["7M"]})
+    );
+}
+
+/// Beach tutors follow the TM bit order, the first listed tutor being the lowest bit
+#[test]
+fn reads_beach_bits_in_order() {
+    let mut romfs = Romfs::default();
+    romfs.set_move(343, "Covet", Move::default());
+    romfs.set_move(441, "Gunk Shot", Move::default());
+    let mut beach_bits = [0; 0xa];
+    // Second and eleventh tutors
+    beach_bits[0] = 0b0000_0010;
+    beach_bits[1] = 0b0000_0100;
+    let personal = Personal {
+        stats: [50; 6],
+        beach_bits,
+        ..Default::default()
+    };
+    romfs.add_species(1, "Grimer", personal);
+    let learnsets = romfs.write().learnsets(Options::default());
+    let showdown = serde_json::to_value(learnsets["grimer"].to_showdown(7, None)).unwrap();
+    assert_eq!(
+        showdown["learnset"],
+        json!({"covet": ["7T"], "gunkshot": ["7T"]})
+    );
+}
+
+/// The written sources start with the generation of `--rom-type`
+#[test]
+fn prefixes_sources_with_the_rom_type_gen() {
+    let learnsets = earthquake_romfs().write().learnsets(Options::default());
+    for rom_type in [RomType::SunMoon, RomType::UltraSunMoon] {
+        let out = TempRom::new();
+        std::fs::create_dir_all(&out.0).unwrap();
+        let options = Options {
+            rom_type,
+            learnset_format: LearnsetFormat::Showdown,
+            ..Default::default()
+        };
+        learnset::write_learnsets(&out.0, &learnsets, &options).unwrap();
+        let written = std::fs::read_to_string(out.0.join("learnsets.json")).unwrap();
+        let written: serde_json::Value = serde_json::from_str(&written).unwrap();
+        let gen = rom_type.gen_digit();
+        assert_eq!(
+            written["sandygast"]["learnset"]["earthquake"],
+            json!([format!("{gen}L1"), format!("{gen}M")]),
+            "{rom_type:?}"
+        );
+    }
+}
+
+/// `--only learnsets` names the species without building the pokedex, the learnsets must
+/// be the ones of a full run
+#[test]
+fn builds_the_same_learnsets_without_the_pokedex() {
+    const ZYGARDE: usize = 718;
+    let mut romfs = Romfs::default();
+    let thousand_arrows = romfs.add_move("Thousand Arrows", Move::default());
+    let core_enforcer = romfs.add_move("Core Enforcer", Move::default());
+    let zygarde = Personal {
+        stats: [108, 100, 121, 95, 81, 95],
+        ..Default::default()
+    };
+    romfs
+        .add_species(ZYGARDE, "Zygarde", zygarde.clone())
+        .level_up = vec![(thousand_arrows, 1)];
+    let first = romfs.add_formes(ZYGARDE, vec![zygarde; 4]);
+    romfs.species[first].level_up = vec![(core_enforcer, 1)];
+    let rom = romfs.write();
+    let options = rom.options(Options::default());
+    let text_files = rom.text_files();
+    let rom_data = RomData::new(&rom.0);
+
+    let (_, full_names) = pokemon::build_pokedex(&rom_data, &text_files, &options).unwrap();
+    let names = pokemon::build_name_map(rom_data.pokemons().unwrap(), &text_files, &options);
+    assert_eq!(names, full_names);
+    let learnsets = |names| {
+        let learnsets = learnset::build_learnsets(&rom_data, &text_files, names, &options);
+        serde_json::to_value(learnsets.unwrap()).unwrap()
+    };
+    let standalone = learnsets(&names);
+    assert_eq!(standalone, learnsets(&full_names));
+    assert_eq!(standalone["zygarde10"][0]["move"], "coreenforcer");
+}
@@ -0,0 +1,57 @@
+//! Checks the input hashes manifest.json records
+
+mod common;
+
+use common::{Romfs, TempRom};
+use serde_json::Value;
+use usum_extractor::{garc_files, manifest, options::Options};
+
+fn manifest_inputs(rom: &TempRom, options: Options) -> Vec<String> {
+    let out = TempRom::new();
+    std::fs::create_dir_all(&out.0).unwrap();
+    manifest::write_manifest(&rom.0, &out.0, &rom.options(options)).unwrap();
+    let manifest: Value =
+        serde_json::from_slice(&std::fs::read(out.0.join("manifest.json")).unwrap()).unwrap();
+    manifest["inputs"]
+        .as_object()
+        .unwrap()
+        .keys()
+        .cloned()
+        .collect()
+}
+
+#[test]
+fn hashes_the_garcs_read() {
+    let rom = Romfs::default().write();
+    let inputs = manifest_inputs(&rom, Options::default());
+    assert_eq!(
+        inputs,
+        [
+            garc_files::POKEMON_STATS,
+            garc_files::EVOLUTIONS,
+            garc_files::MEGA_EVOS,
+            garc_files::LVL_UP_MOVES,
+            garc_files::MOVE,
+            garc_files::TEXT,
+        ]
+    );
+
+    // A partial romfs for the pokedex alone
+    std::fs::remove_file(rom.0.join(garc_files::BASE_PATH).join(garc_files::MOVE)).unwrap();
+    let inputs = manifest_inputs(&rom, Options::default());
+    assert!(!inputs.iter().any(|input| input == garc_files::MOVE));
+    let options = Options {
+        only: vec!["pokedex".to_owned()],
+        ..Default::default()
+    };
+    let inputs = manifest_inputs(&rom, options);
+    assert_eq!(
+        inputs,
+        [
+            garc_files::POKEMON_STATS,
+            garc_files::EVOLUTIONS,
+            garc_files::MEGA_EVOS,
+            garc_files::TEXT,
+        ]
+    );
+}
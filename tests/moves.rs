@@ -0,0 +1,586 @@
+//! Checks the move fields decoded from the ROM and set by the manual patches
+
+mod common;
+
+use common::{Move, Romfs};
+use serde_json::json;
+use usum_extractor::options::Options;
+
+#[test]
+fn sets_ignore_immunity() {
+    let mut romfs = Romfs::default();
+    for name in [
+        "Bide",
+        "Future Sight",
+        "Doom Desire",
+        "Thousand Arrows",
+        "Thunder Wave",
+        "Tackle",
+    ] {
+        romfs.add_move(name, Move::default());
+    }
+    let moves = romfs.write().moves(Options::default());
+
+    for id in ["bide", "futuresight", "doomdesire"] {
+        assert_eq!(moves[id]["ignoreImmunity"], true, "{id}");
+    }
+    assert_eq!(
+        moves["thousandarrows"]["ignoreImmunity"],
+        json!({"Ground": true})
+    );
+    assert_eq!(moves["thunderwave"]["ignoreImmunity"], false);
+    assert_eq!(moves["tackle"]["ignoreImmunity"], json!(null));
+}
+
+#[test]
+fn traps_with_capture_inflict() {
+    let mut romfs = Romfs::default();
+    let bind = Move {
+        category: 1,
+        power: 15,
+        accuracy: 85,
+        pp: 20,
+        inflict: 8,
+        inflict_percent: 100,
+        ..Default::default()
+    };
+    romfs.add_move("Bind", bind);
+    let confusion = Move {
+        category: 2,
+        power: 50,
+        inflict: 6,
+        inflict_percent: 10,
+        ..Default::default()
+    };
+    romfs.add_move("Confusion", confusion);
+    let moves = romfs.write().moves(Options::default());
+
+    assert_eq!(moves["bind"]["volatileStatus"], "partiallytrapped");
+    assert_eq!(moves["bind"]["secondary"], json!(null));
+    assert_eq!(moves["bind"]["secondaries"], json!(null));
+    assert_eq!(moves["confusion"]["volatileStatus"], json!(null));
+    assert_eq!(
+        moves["confusion"]["secondary"],
+        json!({"chance": 10, "volatileStatus": "confusion"})
+    );
+}
+
+#[test]
+fn sets_selfdestruct() {
+    let mut romfs = Romfs::default();
+    for name in ["Explosion", "Final Gambit", "Misty Explosion", "Tackle"] {
+        romfs.add_move(name, Move::default());
+    }
+    let moves = romfs.write().moves(Options::default());
+
+    assert_eq!(moves["explosion"]["selfdestruct"], "always");
+    assert_eq!(moves["finalgambit"]["selfdestruct"], "ifHit");
+    assert_eq!(moves["mistyexplosion"]["selfdestruct"], "always");
+    assert_eq!(moves["tackle"]["selfdestruct"], json!(null));
+}
+
+#[test]
+fn thaws_target() {
+    let mut romfs = Romfs::default();
+    let burning = |move_type| Move {
+        move_type,
+        category: 2,
+        power: 90,
+        inflict: 4,
+        inflict_percent: 10,
+        ..Default::default()
+    };
+    romfs.add_move("Scald", burning(10));
+    romfs.add_move("Flamethrower", burning(9));
+    romfs.add_move("Scorching Sands", burning(4));
+    let moves = romfs.write().moves(Options::default());
+
+    assert_eq!(moves["scald"]["thawsTarget"], true);
+    // Showdown thaws on Fire-type damage without the field
+    assert_eq!(moves["flamethrower"]["thawsTarget"], json!(null));
+    // A TM in these games
+    assert_eq!(moves["scorchingsands"]["thawsTarget"], true);
+}
+
+#[test]
+fn sets_self_switch() {
+    let mut romfs = Romfs::default();
+    for name in ["U-turn", "Volt Switch", "Baton Pass", "Tackle"] {
+        romfs.add_move(name, Move::default());
+    }
+    let moves = romfs.write().moves(Options::default());
+
+    assert_eq!(moves["uturn"]["selfSwitch"], true);
+    assert_eq!(moves["voltswitch"]["selfSwitch"], true);
+    assert_eq!(moves["batonpass"]["selfSwitch"], "copyvolatile");
+    assert_eq!(moves["tackle"]["selfSwitch"], json!(null));
+}
+
+/// Showdown's Z-move power for each base power range, which the ROM's `z_power` follows
+const Z_POWERS: &[(u8, u8)] = &[
+    (40, 100),
+    (60, 120),
+    (70, 140),
+    (80, 160),
+    (90, 175),
+    (100, 180),
+    (110, 185),
+    (120, 190),
+    (130, 195),
+    (150, 200),
+];
+
+#[test]
+fn keeps_rom_z_power() {
+    let mut romfs = Romfs::default();
+    for (power, z_power) in Z_POWERS {
+        let mv = Move {
+            category: 1,
+            power: *power,
+            z_power: *z_power,
+            ..Default::default()
+        };
+        romfs.add_move(&format!("Power {power}"), mv);
+    }
+    romfs.add_move("Splash", Move::default());
+    let moves = romfs.write().moves(Options::default());
+
+    for (power, z_power) in Z_POWERS {
+        let id = format!("power{power}");
+        assert_eq!(moves[&id]["zMove"], json!({"basePower": z_power}), "{id}");
+    }
+    assert_eq!(moves["splash"]["zMove"], json!(null));
+}
+
+#[test]
+fn keeps_priority_sign() {
+    let priorities = [
+        ("Quick Attack", "quickattack", 1),
+        ("Extreme Speed", "extremespeed", 2),
+        ("Protect", "protect", 4),
+        ("Vital Throw", "vitalthrow", -1),
+        ("Whirlwind", "whirlwind", -6),
+        ("Trick Room", "trickroom", -7),
+    ];
+    let mut romfs = Romfs::default();
+    for (name, _, priority) in priorities {
+        let mv = Move {
+            priority,
+            ..Default::default()
+        };
+        romfs.add_move(name, mv);
+    }
+    let moves = romfs.write().moves(Options::default());
+
+    for (_, id, priority) in priorities {
+        assert_eq!(moves[id]["priority"], priority, "{id}");
+    }
+}
+
+#[test]
+fn gives_status_stat_changes_as_primary_boosts() {
+    let stat_change = |target, stat, stat_stage| Move {
+        quality: 2,
+        target,
+        stat: [stat, 0, 0],
+        stat_stage: [stat_stage, 0, 0],
+        stat_percent: [100, 0, 0],
+        ..Default::default()
+    };
+    let mut romfs = Romfs::default();
+    romfs.add_move("Growl", stat_change(5, 1, -1));
+    romfs.add_move("Leer", stat_change(5, 2, -1));
+    romfs.add_move("String Shot", stat_change(5, 5, -2));
+    romfs.add_move("Swords Dance", stat_change(7, 1, 2));
+    let snarl = Move {
+        quality: 6,
+        category: 2,
+        power: 55,
+        ..stat_change(5, 3, -1)
+    };
+    romfs.add_move("Snarl", snarl);
+    let moves = romfs.write().moves(Options::default());
+
+    for (id, boosts) in [
+        ("growl", json!({"atk": -1})),
+        ("leer", json!({"def": -1})),
+        ("stringshot", json!({"spe": -2})),
+        ("swordsdance", json!({"atk": 2})),
+    ] {
+        assert_eq!(moves[id]["boosts"], boosts, "{id}");
+        assert_eq!(moves[id]["secondary"], json!(null), "{id}");
+        assert_eq!(moves[id]["secondaries"], json!(null), "{id}");
+    }
+    assert_eq!(moves["swordsdance"]["target"], "self");
+    // A damaging move's guaranteed drop stays a secondary
+    assert_eq!(moves["snarl"]["boosts"], json!(null));
+    assert_eq!(
+        moves["snarl"]["secondary"],
+        json!({"chance": 100, "boosts": {"spa": -1}})
+    );
+}
+
+#[test]
+fn zeroes_status_move_power() {
+    let mut romfs = Romfs::default();
+    let growl = Move {
+        category: 0,
+        power: 40,
+        ..Default::default()
+    };
+    romfs.add_move("Growl", growl);
+    let tackle = Move {
+        category: 1,
+        power: 40,
+        ..Default::default()
+    };
+    romfs.add_move("Tackle", tackle);
+    let moves = romfs.write().moves(Options::default());
+
+    assert_eq!(moves["growl"]["category"], "Status");
+    assert_eq!(moves["growl"]["basePower"], 0);
+    assert_eq!(moves["tackle"]["basePower"], 40);
+}
+
+#[test]
+fn flags_powder_and_wind_moves() {
+    let mut romfs = Romfs::default();
+    for name in ["Sleep Powder", "Gust", "Tackle"] {
+        romfs.add_move(name, Move::default());
+    }
+    let moves = romfs.write().moves(Options::default());
+
+    assert_eq!(moves["sleeppowder"]["flags"]["powder"], 1);
+    assert_eq!(moves["gust"]["flags"]["wind"], 1);
+    assert_eq!(moves["tackle"]["flags"], json!({}));
+}
+
+#[test]
+fn sets_force_switch() {
+    let mut romfs = Romfs::default();
+    let roar = Move {
+        quality: 12,
+        priority: -6,
+        ..Default::default()
+    };
+    romfs.add_move("Roar", roar);
+    let dragon_tail = Move {
+        category: 1,
+        power: 60,
+        priority: -6,
+        ..Default::default()
+    };
+    romfs.add_move("Dragon Tail", dragon_tail);
+    romfs.add_move("Tackle", Move::default());
+    let moves = romfs.write().moves(Options::default());
+
+    assert_eq!(moves["roar"]["forceSwitch"], true);
+    assert_eq!(moves["dragontail"]["forceSwitch"], true);
+    assert_eq!(moves["tackle"]["forceSwitch"], json!(null));
+}
+
+#[test]
+fn reads_multihit_nibbles() {
+    let mut romfs = Romfs::default();
+    let multihit = |hit_min_max| Move {
+        category: 1,
+        power: 15,
+        hit_min_max,
+        ..Default::default()
+    };
+    romfs.add_move("Double Kick", multihit(0x22));
+    romfs.add_move("Double Slap", multihit(0x52));
+    romfs.add_move("Tackle", multihit(0x11));
+    let moves = romfs.write().moves(Options::default());
+
+    assert_eq!(moves["doublekick"]["multihit"], 2);
+    assert_eq!(moves["doubleslap"]["multihit"], json!([2, 5]));
+    assert_eq!(moves["tackle"]["multihit"], json!(null));
+}
+
+#[test]
+fn maps_doubles_targets() {
+    let mut romfs = Romfs::default();
+    for (name, target) in [
+        ("Earthquake", 4),
+        ("Heat Wave", 5),
+        ("Helping Hand", 2),
+        ("Acupressure", 1),
+        ("Swords Dance", 7),
+    ] {
+        let mv = Move {
+            target,
+            ..Default::default()
+        };
+        romfs.add_move(name, mv);
+    }
+    let moves = romfs.write().moves(Options::default());
+
+    assert_eq!(moves["earthquake"]["target"], "allAdjacent");
+    assert_eq!(moves["heatwave"]["target"], "allAdjacentFoes");
+    assert_eq!(moves["helpinghand"]["target"], "adjacentAlly");
+    assert_eq!(moves["acupressure"]["target"], "adjacentAllyOrSelf");
+    assert_eq!(moves["swordsdance"]["target"], "self");
+}
+
+/// Guaranteed stat changes of status moves go under the move's own `boosts`, never `self`
+#[test]
+fn sets_boosts_on_stat_change_moves() {
+    let mut romfs = Romfs::default();
+    let swords_dance = Move {
+        quality: 2,
+        target: 7,
+        stat: [1, 0, 0],
+        stat_stage: [2, 0, 0],
+        stat_percent: [100, 0, 0],
+        ..Default::default()
+    };
+    romfs.add_move("Swords Dance", swords_dance.clone());
+    let calm_mind = Move {
+        stat: [3, 4, 0],
+        stat_stage: [1, 1, 0],
+        stat_percent: [100, 100, 0],
+        ..swords_dance.clone()
+    };
+    romfs.add_move("Calm Mind", calm_mind);
+    let growl = Move {
+        target: 5,
+        stat_stage: [-1, 0, 0],
+        ..swords_dance
+    };
+    romfs.add_move("Growl", growl);
+    let moves = romfs.write().moves(Options::default());
+
+    assert_eq!(moves["swordsdance"]["boosts"], json!({"atk": 2}));
+    assert_eq!(moves["calmmind"]["boosts"], json!({"spa": 1, "spd": 1}));
+    assert_eq!(moves["growl"]["boosts"], json!({"atk": -1}));
+    for id in ["swordsdance", "calmmind", "growl"] {
+        assert_eq!(moves[id]["self"], json!(null), "{id}");
+    }
+}
+
+#[test]
+fn sets_no_pp_boosts() {
+    let mut romfs = Romfs::default();
+    let pp = |pp| Move {
+        pp,
+        ..Default::default()
+    };
+    romfs.add_move("Sketch", pp(1));
+    romfs.add_move("Tackle", pp(35));
+    let moves = romfs.write().moves(Options::default());
+
+    assert_eq!(moves["sketch"]["noPPBoosts"], true);
+    assert_eq!(moves["sketch"]["pp"], 1);
+    assert_eq!(moves["tackle"]["noPPBoosts"], json!(null));
+    assert_eq!(moves["tackle"]["pp"], 35);
+}
+
+/// A single secondary is written as `secondary`, `secondaries` is left out
+#[test]
+fn collapses_a_single_secondary() {
+    let mut romfs = Romfs::default();
+    let flamethrower = Move {
+        move_type: 9,
+        category: 2,
+        power: 90,
+        inflict: 4,
+        inflict_percent: 10,
+        ..Default::default()
+    };
+    romfs.add_move("Flamethrower", flamethrower);
+    romfs.add_move("Tackle", Move::default());
+    let moves = romfs.write().moves(Options::default());
+
+    assert_eq!(
+        moves["flamethrower"]["secondary"],
+        json!({"chance": 10, "status": "brn"})
+    );
+    assert_eq!(moves["flamethrower"]["secondaries"], json!(null));
+    assert_eq!(moves["tackle"]["secondary"], json!(null));
+}
+
+#[test]
+fn sets_stat_overrides() {
+    let mut romfs = Romfs::default();
+    for name in ["Foul Play", "Psyshock", "Body Press", "Tackle"] {
+        romfs.add_move(name, Move::default());
+    }
+    let moves = romfs.write().moves(Options::default());
+
+    assert_eq!(moves["foulplay"]["overrideOffensivePokemon"], "target");
+    assert_eq!(moves["bodypress"]["overrideOffensiveStat"], "def");
+    assert_eq!(moves["foulplay"]["overrideDefensiveStat"], json!(null));
+    assert_eq!(moves["psyshock"]["overrideDefensiveStat"], "def");
+    assert_eq!(moves["psyshock"]["overrideOffensivePokemon"], json!(null));
+    for field in [
+        "overrideOffensivePokemon",
+        "overrideOffensiveStat",
+        "overrideDefensiveStat",
+    ] {
+        assert_eq!(moves["tackle"][field], json!(null), "{field}");
+    }
+}
+
+/// Bit 11 is `distance` and bit 14 `nonsky`, bit 15 has no name and is only kept in debug
+#[test]
+fn reads_high_flag_bits() {
+    let mut romfs = Romfs::default();
+    let flags = |flags| Move {
+        flags,
+        ..Default::default()
+    };
+    romfs.add_move("Water Pledge", flags(1 << 11 | 1 << 14 | 1 << 15));
+    let rom = romfs.write();
+    let moves = rom.moves(Options::default());
+
+    assert_eq!(
+        moves["waterpledge"]["flags"],
+        json!({"distance": 1, "nonsky": 1})
+    );
+    assert_eq!(moves["waterpledge"]["unknownFlags"], json!(null));
+    let debug = Options {
+        debug: true,
+        ..Default::default()
+    };
+    assert_eq!(rom.moves(debug)["waterpledge"]["unknownFlags"], 1 << 15);
+}
+
+/// Signature Z-moves carry their crystal, the base move lists them. The ROM's `z_move`
+/// only points at the generic Z-move of the type.
+#[test]
+fn links_signature_z_moves() {
+    let mut romfs = Romfs::default();
+    let electric = |power| Move {
+        move_type: 12,
+        category: 2,
+        power,
+        ..Default::default()
+    };
+    let gigavolt_havoc = romfs.add_move("Gigavolt Havoc", electric(1));
+    let thunderbolt = Move {
+        z_move: gigavolt_havoc as u16,
+        z_power: 175,
+        ..electric(90)
+    };
+    romfs.add_move("Thunderbolt", thunderbolt);
+    romfs.add_move("10,000,000 Volt Thunderbolt", electric(195));
+    romfs.add_move("Stoked Sparksurfer", electric(175));
+    let moves = romfs.write().moves(Options::default());
+
+    assert_eq!(
+        moves["thunderbolt"]["signatureZMoves"],
+        json!(["10000000voltthunderbolt", "stokedsparksurfer"])
+    );
+    assert_eq!(moves["thunderbolt"]["isZ"], json!(null));
+    assert_eq!(moves["10000000voltthunderbolt"]["isZ"], "pikashuniumz");
+    assert_eq!(moves["stokedsparksurfer"]["isZ"], "aloraichiumz");
+    assert_eq!(moves["gigavolthavoc"]["isZ"], "electriumz");
+}
+
+/// Inflict ids past the statuses name a volatile, an unmapped one keeps its number. A
+/// damaging move's chance to inflict it is a secondary.
+#[test]
+fn names_inflicted_volatiles() {
+    let mut romfs = Romfs::default();
+    let inflict = |inflict, inflict_percent| Move {
+        category: 2,
+        power: 65,
+        inflict,
+        inflict_percent,
+        ..Default::default()
+    };
+    romfs.add_move("Psybeam", inflict(6, 10));
+    romfs.add_move("Mystery", inflict(42, 30));
+    let moves = romfs.write().moves(Options::default());
+
+    for (id, chance, volatile) in [("psybeam", 10, "confusion"), ("mystery", 30, "42")] {
+        assert_eq!(
+            moves[id]["secondary"],
+            json!({"chance": chance, "volatileStatus": volatile}),
+            "{id}"
+        );
+        assert_eq!(moves[id]["volatileStatus"], json!(null), "{id}");
+    }
+}
+
+/// Status moves inflict their status or volatile outright, at the top level like
+/// Showdown's Will-O-Wisp and Confuse Ray, rather than as a secondary
+#[test]
+fn status_moves_inflict_at_top_level() {
+    let mut romfs = Romfs::default();
+    let inflict = |inflict| Move {
+        category: 0,
+        accuracy: 100,
+        inflict,
+        ..Default::default()
+    };
+    romfs.add_move("Confuse Ray", inflict(6));
+    romfs.add_move("Attract", inflict(7));
+    romfs.add_move("Will-O-Wisp", inflict(4));
+    romfs.add_move("Leech Seed", inflict(18));
+    romfs.add_move("Toxic", inflict(5));
+    let moves = romfs.write().moves(Options::default());
+
+    for (id, volatile) in [
+        ("confuseray", "confusion"),
+        ("attract", "attract"),
+        ("leechseed", "leechseed"),
+    ] {
+        assert_eq!(moves[id]["volatileStatus"], volatile, "{id}");
+        assert_eq!(moves[id]["status"], json!(null), "{id}");
+        assert_eq!(moves[id]["secondary"], json!(null), "{id}");
+    }
+    for (id, status) in [("willowisp", "brn"), ("toxic", "tox")] {
+        assert_eq!(moves[id]["status"], status, "{id}");
+        assert_eq!(moves[id]["volatileStatus"], json!(null), "{id}");
+        assert_eq!(moves[id]["secondary"], json!(null), "{id}");
+    }
+}
+
+/// The ROM stores Curse as targeting the user, Showdown's target is the Ghost-type one
+#[test]
+fn sets_curse_non_ghost_target() {
+    let mut romfs = Romfs::default();
+    let curse = Move {
+        move_type: 7,
+        target: 7,
+        ..Default::default()
+    };
+    romfs.add_move("Curse", curse);
+    romfs.add_move("Tackle", Move::default());
+    let moves = romfs.write().moves(Options::default());
+
+    assert_eq!(moves["curse"]["target"], "randomNormal");
+    assert_eq!(moves["curse"]["nonGhostTarget"], "self");
+    assert_eq!(moves["tackle"]["nonGhostTarget"], json!(null));
+}
+
+#[test]
+fn flags_variable_type_moves() {
+    let mut romfs = Romfs::default();
+    for name in ["Weather Ball", "Judgment", "Tackle"] {
+        romfs.add_move(name, Move::default());
+    }
+    let moves = romfs.write().moves(Options::default());
+
+    assert_eq!(moves["weatherball"]["variableType"], true);
+    assert_eq!(moves["judgment"]["variableType"], true);
+    assert_eq!(moves["judgment"]["type"], "Normal");
+    assert_eq!(moves["tackle"]["variableType"], json!(null));
+}
+
+#[test]
+fn flags_moves_sleep_talk_cant_call() {
+    let mut romfs = Romfs::default();
+    for name in ["Solar Beam", "Phantom Force", "Chatter", "Tackle"] {
+        romfs.add_move(name, Move::default());
+    }
+    let moves = romfs.write().moves(Options::default());
+
+    for id in ["solarbeam", "phantomforce", "chatter"] {
+        assert_eq!(moves[id]["flags"]["nosleeptalk"], 1, "{id}");
+    }
+    assert_eq!(moves["chatter"]["flags"]["nosketch"], 1);
+    assert_eq!(moves["tackle"]["flags"], json!({}));
+}
@@ -0,0 +1,67 @@
+//! Checks the command line parsing
+
+mod common;
+
+use std::{fs, path::Path, process::Command};
+
+use common::TempRom;
+use usum_extractor::{
+    language::Language,
+    options::{Options, RomType},
+};
+
+fn parse(args: &[&str]) -> color_eyre::Result<Options> {
+    Options::from_args(args.iter().map(|arg| (*arg).to_owned()))
+}
+
+#[test]
+fn verify_keys_takes_only_the_rom_path() {
+    let options = parse(&["rom", "--verify-keys", "1=Bulbasaur"]).unwrap();
+    assert_eq!(options.rom_path, Path::new("rom"));
+    assert_eq!(options.verify_keys, Some((1, "Bulbasaur".to_owned())));
+    assert!(parse(&["rom"]).is_err());
+}
+
+#[test]
+fn config_values_are_overridden_by_flags() {
+    let dir = TempRom::new();
+    fs::create_dir_all(&dir.0).unwrap();
+    let config = dir.0.join("config.toml");
+    fs::write(
+        &config,
+        "rom = \"config-rom\"\nout = \"config-out\"\nrom-type = \"sm\"\n\
+         languages = [\"fr\", \"de\"]\nonly = [\"moves\"]\nforce = true\n",
+    )
+    .unwrap();
+    let parse_with_config = |args: &[&str]| {
+        let args = args.iter().map(|arg| (*arg).to_owned());
+        Options::from_args_with_config(args, Some(config.clone())).unwrap()
+    };
+
+    let options = parse_with_config(&[]);
+    assert_eq!(options.rom_path, Path::new("config-rom"));
+    assert_eq!(options.out_path, Path::new("config-out"));
+    assert_eq!(options.rom_type, RomType::SunMoon);
+    assert_eq!(options.languages, [Language::French, Language::German]);
+    assert_eq!(options.only, ["moves"]);
+    assert!(options.force);
+
+    let options = parse_with_config(&["rom", "out", "--languages", "en", "--only", "pokedex"]);
+    assert_eq!(options.rom_path, Path::new("rom"));
+    assert_eq!(options.out_path, Path::new("out"));
+    assert_eq!(options.rom_type, RomType::SunMoon);
+    assert_eq!(options.languages, [Language::English]);
+    assert_eq!(options.only, ["pokedex"]);
+    assert!(options.force);
+}
+
+#[test]
+fn exits_on_a_bad_argument() {
+    let output = Command::new(env!("CARGO_BIN_EXE_usum-extractor"))
+        .args(["rom", "out", "--bogus"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "error: unknown option --bogus\n");
+}
@@ -0,0 +1,410 @@
+//! Runs the builders end to end on a small synthetic romfs
+
+mod common;
+
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use common::{
+    evolutions, level_up_moves, move_container, text_file, write_garc, Move, Personal, Romfs,
+};
+use serde_json::json;
+use usum_extractor::{
+    build_abilities, garc, garc_files, learnset, moves, options::Options, pokemon, text::TextFile,
+    text_ids, RomData,
+};
+
+const TYPE_NAMES: [&str; 18] = [
+    "Normal", "Fighting", "Flying", "Poison", "Ground", "Rock", "Bug", "Ghost", "Steel", "Fire",
+    "Water", "Grass", "Electric", "Psychic", "Ice", "Dragon", "Dark", "Fairy",
+];
+
+/// Writes the text GARC, with the moves Tackle, Growl and the three Pledges
+fn write_texts(rom_path: &Path, species_names: &[&str]) {
+    let mut texts = vec![text_file(&[]); text_ids::MOVE_NAMES + 1];
+    texts[text_ids::SPECIES_NAMES] = text_file(species_names);
+    texts[text_ids::ITEM_NAMES] = text_file(&["None"]);
+    texts[text_ids::ABILITY_NAMES] =
+        text_file(&["-", "Overgrow", "Chlorophyll", "Levitate", "Sturdy"]);
+    texts[text_ids::ABILITY_DESCS] = text_file(&[
+        "",
+        "Powers up Grass-type moves when the Pokémon's HP is low.",
+        "Boosts the Pokémon's Speed stat in harsh sunlight.",
+        "By floating in the air, the Pokémon receives full immu\u{AD}\nnity to all Ground-type moves.",
+        "It cannot be knocked out with one hit. One-hit KO moves cannot knock it out, either.",
+    ]);
+    texts[text_ids::MOVE_NAMES] = text_file(&[
+        "-",
+        "Tackle",
+        "Growl",
+        "Grass Pledge",
+        "Fire Pledge",
+        "Water Pledge",
+    ]);
+    texts[text_ids::MOVE_DESCS] = text_file(&[
+        "",
+        "A physical attack in which the user charges and\nslams into the target.",
+        "The user growls in an endearing way, lowering the Attack stats of opposing Pokémon.",
+        "A column of grass hits the target.",
+        "A column of fire hits the target.",
+        "A column of water hits the target.",
+    ]);
+    texts[text_ids::TYPE_NAMES] = text_file(&TYPE_NAMES);
+    write_garc(rom_path, garc_files::TEXT, &texts);
+}
+
+/// Writes a romfs with Bulbasaur evolving into Ivysaur at level 16, and the moves Tackle,
+/// Growl and the three Pledges
+fn write_romfs(rom_path: &Path) {
+    write_texts(rom_path, &["Egg", "Bulbasaur", "Ivysaur"]);
+
+    let bulbasaur = Personal {
+        stats: [45, 49, 49, 45, 65, 65],
+        types: (11, 3),
+        gender: 31,
+        egg_groups: [1, 7],
+        abilities: [1, 1, 2],
+        weight: 69,
+        ..Default::default()
+    };
+    let ivysaur = Personal {
+        stats: [60, 62, 63, 60, 80, 80],
+        weight: 130,
+        ..bulbasaur
+    };
+    write_garc(
+        rom_path,
+        garc_files::POKEMON_STATS,
+        &[
+            Personal::default().to_bytes(),
+            bulbasaur.to_bytes(),
+            ivysaur.to_bytes(),
+        ],
+    );
+    write_garc(
+        rom_path,
+        garc_files::EVOLUTIONS,
+        &[
+            evolutions(&[]),
+            evolutions(&[(4, 16, 2, 16)]),
+            evolutions(&[]),
+        ],
+    );
+    write_garc(rom_path, garc_files::MEGA_EVOS, &[vec![], vec![], vec![]]);
+    write_garc(
+        rom_path,
+        garc_files::LVL_UP_MOVES,
+        &[
+            level_up_moves(&[]),
+            level_up_moves(&[(1, 1), (2, 3)]),
+            level_up_moves(&[(1, 1), (2, 1)]),
+        ],
+    );
+
+    let tackle = Move {
+        category: 1,
+        power: 40,
+        accuracy: 100,
+        pp: 35,
+        ..Default::default()
+    };
+    let growl = Move {
+        quality: 2,
+        accuracy: 100,
+        pp: 40,
+        target: 5,
+        stat: [1, 0, 0],
+        stat_stage: [-1, 0, 0],
+        stat_percent: [100, 0, 0],
+        ..Default::default()
+    };
+    let pledge = |move_type| Move {
+        move_type,
+        category: 2,
+        power: 80,
+        accuracy: 100,
+        pp: 10,
+        ..Default::default()
+    };
+    write_garc(
+        rom_path,
+        garc_files::MOVE,
+        &[move_container(&[
+            Move::default(),
+            tackle,
+            growl,
+            pledge(11),
+            pledge(9),
+            pledge(10),
+        ])],
+    );
+}
+
+struct TempRom(PathBuf);
+
+impl TempRom {
+    fn new(name: &str) -> TempRom {
+        TempRom(std::env::temp_dir().join(format!("usum-{name}-{}", std::process::id())))
+    }
+}
+
+impl Drop for TempRom {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn builds_synthetic_romfs() {
+    let rom = TempRom::new("pipeline");
+    let rom_path = &rom.0;
+    write_romfs(rom_path);
+
+    let options = Options {
+        rom_path: rom_path.clone(),
+        ..Default::default()
+    };
+    let text_path = rom_path.join(garc_files::BASE_PATH).join(garc_files::TEXT);
+    let text_files = garc::read_files::<TextFile>(&garc::open(&text_path).unwrap()).unwrap();
+    let rom = RomData::new(rom_path);
+
+    let (pokedex, names) = pokemon::build_pokedex(&rom, &text_files, &options).unwrap();
+    let pokedex = serde_json::to_value(pokedex).unwrap();
+    let ids: Vec<&String> = pokedex.as_object().unwrap().keys().collect();
+    assert_eq!(ids, ["bulbasaur", "ivysaur"]);
+    let bulbasaur = &pokedex["bulbasaur"];
+    assert_eq!(bulbasaur["num"], 1);
+    assert_eq!(bulbasaur["types"], json!(["Grass", "Poison"]));
+    assert_eq!(
+        bulbasaur["abilities"],
+        json!({"0": "Overgrow", "H": "Chlorophyll"})
+    );
+    assert_eq!(bulbasaur["weightkg"], json!(6.9f32));
+    assert_eq!(bulbasaur["evos"], json!(["Ivysaur"]));
+    assert_eq!(pokedex["ivysaur"]["prevo"], "Bulbasaur");
+    assert_eq!(pokedex["ivysaur"]["evoLevel"], 16);
+
+    let moves =
+        serde_json::to_value(moves::build_moves(&rom, &text_files, &options).unwrap()).unwrap();
+    let tackle = &moves["tackle"];
+    assert_eq!(tackle["name"], "Tackle");
+    assert_eq!(
+        tackle["desc"],
+        "A physical attack in which the user charges and slams into the target."
+    );
+    assert_eq!(tackle["category"], "Physical");
+    assert_eq!(tackle["basePower"], 40);
+    assert_eq!(tackle["pp"], 35);
+    let growl = &moves["growl"];
+    assert_eq!(growl["category"], "Status");
+    assert_eq!(growl["boosts"], json!({"atk": -1}));
+    assert_eq!(growl["target"], "allAdjacentFoes");
+    assert_eq!(tackle["isPledge"], json!(null));
+    for id in ["grasspledge", "firepledge", "waterpledge"] {
+        assert_eq!(moves[id]["basePower"], 80, "{id}");
+        assert_eq!(moves[id]["isPledge"], true, "{id}");
+    }
+
+    let learnsets = learnset::build_learnsets(&rom, &text_files, &names, &options).unwrap();
+    let showdown = |id: &str| serde_json::to_value(learnsets[id].to_showdown(7, None)).unwrap();
+    let bulbasaur = showdown("bulbasaur");
+    assert_eq!(bulbasaur["learnset"]["tackle"], json!(["7L1"]));
+    assert_eq!(bulbasaur["learnset"]["growl"], json!(["7L3"]));
+    assert_eq!(showdown("ivysaur")["learnset"]["growl"], json!(["7L1"]));
+}
+
+/// Rotom's appliance formes, with their personal data after the last species
+#[test]
+fn reads_rotom_forme_types() {
+    const ROTOM: usize = 479;
+    const SPECIES_COUNT: usize = 808;
+    let formes = [
+        ("rotomheat", 9, "Fire"),
+        ("rotomwash", 10, "Water"),
+        ("rotomfrost", 14, "Ice"),
+        ("rotomfan", 2, "Flying"),
+        ("rotommow", 11, "Grass"),
+    ];
+
+    let rom = TempRom::new("rotom");
+    let rom_path = &rom.0;
+    let mut species_names = vec![""; SPECIES_COUNT];
+    species_names[0] = "Egg";
+    species_names[ROTOM] = "Rotom";
+    write_texts(rom_path, &species_names);
+
+    let rotom = |second_type| Personal {
+        stats: [50, 65, 107, 86, 105, 107],
+        types: (12, second_type),
+        gender: 255,
+        egg_groups: [11, 11],
+        abilities: [3, 3, 3],
+        weight: 3,
+        form_count: formes.len() as u8 + 1,
+        form_stats_id: SPECIES_COUNT as u16,
+        ..Default::default()
+    };
+    let mut personal = vec![Personal::default().to_bytes(); SPECIES_COUNT];
+    personal[ROTOM] = rotom(7).to_bytes();
+    for (_, second_type, _) in formes {
+        personal.push(
+            Personal {
+                form_count: 0,
+                form_stats_id: 0,
+                ..rotom(second_type)
+            }
+            .to_bytes(),
+        );
+    }
+    write_garc(rom_path, garc_files::POKEMON_STATS, &personal);
+    write_garc(
+        rom_path,
+        garc_files::EVOLUTIONS,
+        &vec![evolutions(&[]); personal.len()],
+    );
+    write_garc(
+        rom_path,
+        garc_files::MEGA_EVOS,
+        &vec![vec![]; personal.len()],
+    );
+
+    let options = Options {
+        rom_path: rom_path.clone(),
+        ..Default::default()
+    };
+    let text_path = rom_path.join(garc_files::BASE_PATH).join(garc_files::TEXT);
+    let text_files = garc::read_files::<TextFile>(&garc::open(&text_path).unwrap()).unwrap();
+    let (pokedex, _) =
+        pokemon::build_pokedex(&RomData::new(rom_path), &text_files, &options).unwrap();
+    let pokedex = serde_json::to_value(pokedex).unwrap();
+
+    assert_eq!(pokedex["rotom"]["types"], json!(["Electric", "Ghost"]));
+    for (id, _, second_type) in formes {
+        assert_eq!(
+            pokedex[id]["types"],
+            json!(["Electric", second_type]),
+            "{id}"
+        );
+        assert_eq!(pokedex[id]["abilities"], json!({"0": "Levitate"}), "{id}");
+    }
+}
+
+#[test]
+fn flags_breakable_abilities() {
+    let rom = TempRom::new("abilities");
+    let rom_path = &rom.0;
+    write_texts(rom_path, &["Egg"]);
+    let text_path = rom_path.join(garc_files::BASE_PATH).join(garc_files::TEXT);
+    let text_files = garc::read_files::<TextFile>(&garc::open(&text_path).unwrap()).unwrap();
+
+    let abilities = build_abilities(&text_files, &Options::default()).unwrap();
+    assert!(abilities["sturdy"].flags.is_none());
+
+    let options = Options {
+        ability_flags: true,
+        ..Default::default()
+    };
+    let abilities = serde_json::to_value(build_abilities(&text_files, &options).unwrap()).unwrap();
+    assert_eq!(abilities["sturdy"]["flags"], json!({"breakable": 1}));
+    assert_eq!(abilities["overgrow"]["flags"], json!({}));
+}
+
+/// A word split by a soft hyphen at a line break is joined back
+#[test]
+fn joins_hyphenated_ability_desc_lines() {
+    let rom = TempRom::new("ability-descs");
+    let rom_path = &rom.0;
+    write_texts(rom_path, &["Egg"]);
+    let text_path = rom_path.join(garc_files::BASE_PATH).join(garc_files::TEXT);
+    let text_files = garc::read_files::<TextFile>(&garc::open(&text_path).unwrap()).unwrap();
+
+    let abilities = build_abilities(&text_files, &Options::default()).unwrap();
+    assert_eq!(
+        abilities["levitate"].desc,
+        "By floating in the air, the Pokemon receives full immunity to all Ground-type moves."
+    );
+}
+
+/// A run over several languages only reads the text GARC again for each
+#[test]
+fn decodes_shared_garcs_once() {
+    let mut romfs = Romfs::default();
+    let tackle = romfs.add_move("Tackle", Move::default());
+    let personal = Personal {
+        stats: [45, 49, 49, 45, 65, 65],
+        ..Default::default()
+    };
+    romfs.add_species(1, "Bulbasaur", personal).level_up = vec![(tackle, 1)];
+    let rom = romfs.write();
+    let options = rom.options(Options::default());
+    let text_files = rom.text_files();
+
+    let rom_data = RomData::new(&rom.0);
+    let (_, names) = pokemon::build_pokedex(&rom_data, &text_files, &options).unwrap();
+    learnset::build_learnsets(&rom_data, &text_files, &names, &options).unwrap();
+    moves::build_moves(&rom_data, &text_files, &options).unwrap();
+    std::fs::remove_dir_all(rom.0.join(garc_files::BASE_PATH).join("0/1")).unwrap();
+
+    let (pokedex, names) = pokemon::build_pokedex(&rom_data, &text_files, &options).unwrap();
+    assert!(pokedex.contains_key("bulbasaur"));
+    let learnsets = learnset::build_learnsets(&rom_data, &text_files, &names, &options).unwrap();
+    assert!(learnsets.contains_key("bulbasaur"));
+    let moves = moves::build_moves(&rom_data, &text_files, &options).unwrap();
+    assert!(moves.contains_key("tackle"));
+}
+
+/// `--only` opens the GARCs of the selected outputs alone, a missing move GARC only fails
+/// the moves
+#[test]
+fn runs_only_pokedex_without_the_move_garc() {
+    let mut romfs = Romfs::default();
+    let bulbasaur = Personal {
+        stats: [45, 49, 49, 45, 65, 65],
+        ..Default::default()
+    };
+    romfs.add_species(1, "Bulbasaur", bulbasaur);
+    let rom = romfs.write();
+    std::fs::remove_file(rom.0.join(garc_files::BASE_PATH).join(garc_files::MOVE)).unwrap();
+    let out = TempRom::new("only-out");
+    std::fs::create_dir_all(&out.0).unwrap();
+    let run = |only| {
+        Command::new(env!("CARGO_BIN_EXE_usum-extractor"))
+            .args([&rom.0, &out.0])
+            .args(["--only", only])
+            .output()
+            .unwrap()
+    };
+
+    let output = run("pokedex");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let pokedex = std::fs::read_to_string(out.0.join("pokedex.json")).unwrap();
+    let pokedex: serde_json::Value = serde_json::from_str(&pokedex).unwrap();
+    assert_eq!(pokedex["bulbasaur"]["num"], 1);
+    assert!(!run("moves").status.success());
+    assert!(!out.0.join("moves.json").exists());
+}
+
+/// Refusing to write over foreign files is an error message, not a panic
+#[test]
+fn refuses_an_out_dir_with_foreign_files() {
+    let rom = Romfs::default().write();
+    let out = TempRom::new("foreign-out");
+    std::fs::create_dir_all(&out.0).unwrap();
+    std::fs::write(out.0.join("notes.txt"), "mine").unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_usum-extractor"))
+        .args([&rom.0, &out.0])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.starts_with("error: "), "{stderr}");
+    assert!(stderr.contains("(notes.txt), pass --force"), "{stderr}");
+    assert!(!stderr.contains("panicked"), "{stderr}");
+}
@@ -0,0 +1,711 @@
+//! Checks the pokedex entries the manual patches and forme tables set
+
+mod common;
+
+use common::{write_garc, Move, Personal, Romfs};
+use serde_json::json;
+use usum_extractor::{
+    garc, garc_files,
+    language::Language,
+    options::{Options, RomType},
+    pokemon,
+    text::TextFile,
+    RomData,
+};
+
+#[test]
+fn maps_zygarde_formes() {
+    const ZYGARDE: usize = 718;
+    let mut romfs = Romfs::default();
+    let aura_break = romfs.add_ability("Aura Break");
+    let power_construct = romfs.add_ability("Power Construct");
+    let zygarde = |stats, ability| Personal {
+        stats,
+        types: (15, 4),
+        gender: 255,
+        egg_groups: [15, 15],
+        abilities: [ability; 3],
+        weight: 3050,
+        ..Default::default()
+    };
+    let fifty = [108, 100, 121, 95, 81, 95];
+    let ten = [54, 100, 71, 115, 61, 85];
+    romfs.add_species(ZYGARDE, "Zygarde", zygarde(fifty, aura_break));
+    romfs.add_formes(
+        ZYGARDE,
+        vec![
+            zygarde(ten, aura_break),
+            zygarde(ten, power_construct),
+            zygarde(fifty, power_construct),
+            zygarde([216, 100, 121, 85, 91, 95], power_construct),
+        ],
+    );
+    let rom = romfs.write();
+    let pokedex = rom.pokedex(Options::default());
+
+    let both = json!({"0": "Aura Break", "S": "Power Construct"});
+    assert_eq!(pokedex["zygarde"]["abilities"], both);
+    assert_eq!(pokedex["zygarde10"]["name"], "Zygarde-10%");
+    assert_eq!(pokedex["zygarde10"]["abilities"], both);
+    assert_eq!(pokedex["zygarde10"]["baseStats"]["hp"], 54);
+    let complete = &pokedex["zygardecomplete"];
+    assert_eq!(complete["battleOnly"], json!(["Zygarde", "Zygarde-10%"]));
+    assert_eq!(complete["requiredAbility"], "Power Construct");
+}
+
+#[test]
+fn reads_oricorio_style_types() {
+    const ORICORIO: usize = 741;
+    let mut romfs = Romfs::default();
+    let dancer = romfs.add_ability("Dancer");
+    let oricorio = |first_type| Personal {
+        stats: [75, 70, 70, 93, 98, 70],
+        types: (first_type, 2),
+        abilities: [dancer; 3],
+        ..Default::default()
+    };
+    romfs.add_species(ORICORIO, "Oricorio", oricorio(9));
+    romfs.add_formes(ORICORIO, vec![oricorio(12), oricorio(13), oricorio(7)]);
+    let pokedex = romfs.write().pokedex(Options::default());
+
+    for (id, first_type) in [
+        ("oricorio", "Fire"),
+        ("oricoriopompom", "Electric"),
+        ("oricoriopau", "Psychic"),
+        ("oricoriosensu", "Ghost"),
+    ] {
+        assert_eq!(pokedex[id]["types"], json!([first_type, "Flying"]), "{id}");
+        assert_eq!(pokedex[id]["abilities"], json!({"0": "Dancer"}), "{id}");
+    }
+}
+
+#[test]
+fn reads_lycanroc_form_abilities() {
+    const LYCANROC: usize = 745;
+    let mut romfs = Romfs::default();
+    let mut ability = |name| romfs.add_ability(name);
+    let keen_eye = ability("Keen Eye");
+    let sand_rush = ability("Sand Rush");
+    let steadfast = ability("Steadfast");
+    let vital_spirit = ability("Vital Spirit");
+    let no_guard = ability("No Guard");
+    let tough_claws = ability("Tough Claws");
+    let lycanroc = |abilities| Personal {
+        stats: [75, 115, 65, 112, 55, 65],
+        types: (5, 5),
+        abilities,
+        ..Default::default()
+    };
+    romfs.add_species(
+        LYCANROC,
+        "Lycanroc",
+        lycanroc([keen_eye, sand_rush, steadfast]),
+    );
+    romfs.add_formes(
+        LYCANROC,
+        vec![
+            lycanroc([keen_eye, vital_spirit, no_guard]),
+            lycanroc([tough_claws; 3]),
+        ],
+    );
+    let pokedex = romfs.write().pokedex(Options::default());
+
+    assert_eq!(
+        pokedex["lycanroc"]["abilities"],
+        json!({"0": "Keen Eye", "1": "Sand Rush", "H": "Steadfast"})
+    );
+    assert_eq!(
+        pokedex["lycanrocmidnight"]["abilities"],
+        json!({"0": "Keen Eye", "1": "Vital Spirit", "H": "No Guard"})
+    );
+    assert_eq!(
+        pokedex["lycanrocdusk"]["abilities"],
+        json!({"0": "Tough Claws"})
+    );
+    for id in ["lycanroc", "lycanrocmidnight", "lycanrocdusk"] {
+        assert_eq!(pokedex[id]["types"], json!(["Rock"]), "{id}");
+    }
+}
+
+#[test]
+fn lists_wild_held_items() {
+    let mut romfs = Romfs::default();
+    let oran_berry = romfs.add_item("Oran Berry");
+    let light_ball = romfs.add_item("Light Ball");
+    let pikachu = Personal {
+        stats: [35, 55, 40, 90, 50, 50],
+        types: (12, 12),
+        items: [oran_berry, oran_berry, light_ball],
+        ..Default::default()
+    };
+    romfs.add_species(25, "Pikachu", pikachu.clone());
+    let raichu = Personal {
+        items: [0; 3],
+        ..pikachu
+    };
+    romfs.add_species(26, "Raichu", raichu);
+    let pokedex = romfs.write().pokedex(Options::default());
+
+    assert_eq!(
+        pokedex["pikachu"]["heldItems"],
+        json!(["Oran Berry", "Light Ball"])
+    );
+    assert_eq!(pokedex["raichu"]["heldItems"], json!(null));
+}
+
+/// Formes follow their base species in `form_id` order
+#[test]
+fn orders_formes_after_their_base() {
+    const DEOXYS: usize = 386;
+    let mut romfs = Romfs::default();
+    let deoxys = Personal {
+        stats: [50, 150, 50, 150, 50, 150],
+        types: (13, 13),
+        ..Default::default()
+    };
+    romfs.add_species(DEOXYS, "Deoxys", deoxys.clone());
+    romfs.add_formes(DEOXYS, vec![deoxys.clone(); 3]);
+    let pokedex = romfs.write().pokedex(Options::default());
+
+    let ids: Vec<&String> = pokedex.as_object().unwrap().keys().collect();
+    let deoxys = ids.iter().position(|id| *id == "deoxys").unwrap();
+    assert_eq!(
+        ids[deoxys..deoxys + 4],
+        ["deoxys", "deoxysattack", "deoxysdefense", "deoxysspeed"]
+    );
+}
+
+/// The ROM's gender glyphs stay symbols in the names, the ids get Showdown's letters
+#[test]
+fn names_both_nidoran() {
+    let mut romfs = Romfs::default();
+    let nidoran = Personal {
+        stats: [55, 47, 52, 40, 41, 46],
+        types: (3, 3),
+        ..Default::default()
+    };
+    romfs.add_species(29, "Nidoran\u{E08F}", nidoran.clone());
+    romfs.add_species(32, "Nidoran\u{E08E}", nidoran);
+    let pokedex = romfs.write().pokedex(Options::default());
+
+    assert_eq!(pokedex["nidoranf"]["name"], "Nidoran♀");
+    assert_eq!(pokedex["nidoranm"]["name"], "Nidoran♂");
+}
+
+/// The caps collapse into `cosmeticFormes`, base Pikachu keeps its own stats and learnset
+#[test]
+fn keeps_base_pikachu_with_caps() {
+    const PIKACHU: usize = 25;
+    let mut romfs = Romfs::default();
+    let thunder_shock = romfs.add_move("Thunder Shock", Move::default());
+    let volt_tackle = romfs.add_move("Volt Tackle", Move::default());
+    let pikachu = Personal {
+        stats: [35, 55, 40, 90, 50, 50],
+        types: (12, 12),
+        ..Default::default()
+    };
+    romfs
+        .add_species(PIKACHU, "Pikachu", pikachu.clone())
+        .level_up = vec![(thunder_shock, 1)];
+    let cap = Personal {
+        stats: [45, 80, 50, 120, 75, 60],
+        ..pikachu
+    };
+    let first = romfs.add_formes(PIKACHU, vec![cap; 7]);
+    for cap in &mut romfs.species[first..] {
+        cap.level_up = vec![(volt_tackle, 1)];
+    }
+    let rom = romfs.write();
+    let pokedex = rom.pokedex(Options::default());
+    let learnsets = rom.learnsets(Options::default());
+
+    let base = &pokedex["pikachu"];
+    assert_eq!(base["baseStats"]["hp"], 35);
+    assert_eq!(base["baseStats"]["spe"], 90);
+    assert_eq!(base["formes"], json!(null));
+    assert_eq!(base["cosmeticFormes"].as_array().unwrap().len(), 7);
+    assert_eq!(base["cosmeticFormes"][0], "Pikachu-Original");
+    assert_eq!(pokedex["pikachuoriginal"], json!(null));
+    let learnset = serde_json::to_value(&learnsets["pikachu"]).unwrap();
+    assert_eq!(
+        learnset,
+        json!([{"move": "thundershock", "how": "lvl", "level": 1}])
+    );
+}
+
+#[test]
+fn reads_egg_groups() {
+    let mut romfs = Romfs::default();
+    let species = |egg_groups| Personal {
+        stats: [100; 6],
+        egg_groups,
+        ..Default::default()
+    };
+    romfs.add_species(1, "Bulbasaur", species([1, 7]));
+    romfs.add_species(150, "Mewtwo", species([15, 15]));
+    // A blank slot reads as "---", which Showdown doesn't know
+    romfs.add_species(151, "Mew", species([0, 0]));
+    let pokedex = romfs.write().pokedex(Options::default());
+
+    assert_eq!(
+        pokedex["bulbasaur"]["eggGroups"],
+        json!(["Monster", "Grass"])
+    );
+    assert_eq!(pokedex["mewtwo"]["eggGroups"], json!(["Undiscovered"]));
+    assert_eq!(pokedex["mew"]["eggGroups"], json!(["Undiscovered"]));
+}
+
+/// A dump whose Arceus forme stats entries all say Normal still gets each forme's type
+/// from `FORME_TYPES`
+#[test]
+fn overrides_arceus_forme_types() {
+    const ARCEUS: usize = 493;
+    let mut romfs = Romfs::default();
+    let arceus = Personal {
+        stats: [120; 6],
+        types: (0, 0),
+        ..Default::default()
+    };
+    romfs.add_species(ARCEUS, "Arceus", arceus.clone());
+    romfs.add_formes(ARCEUS, vec![arceus; 17]);
+    let pokedex = romfs.write().pokedex(Options::default());
+
+    assert_eq!(pokedex["arceus"]["types"], json!(["Normal"]));
+    assert_eq!(pokedex["arceusfire"]["types"], json!(["Fire"]));
+    assert_eq!(pokedex["arceuswater"]["types"], json!(["Water"]));
+}
+
+/// The evolution argument is a move for method 21 and a type for method 29
+#[test]
+fn reads_move_and_type_evolution_arguments() {
+    let mut romfs = Romfs::default();
+    let ancient_power = romfs.set_move(246, "Ancient Power", Move::default());
+    let species = Personal {
+        stats: [65; 6],
+        ..Default::default()
+    };
+    romfs
+        .add_species(114, "Tangela", species.clone())
+        .evolutions = vec![(21, ancient_power as u16, 465, 0)];
+    romfs.add_species(133, "Eevee", species.clone()).evolutions = vec![(29, 17, 700, 0)];
+    romfs.add_species(465, "Tangrowth", species.clone());
+    romfs.add_species(700, "Sylveon", species);
+    let pokedex = romfs.write().pokedex(Options::default());
+
+    let tangrowth = &pokedex["tangrowth"];
+    assert_eq!(tangrowth["evoType"], "levelMove");
+    assert_eq!(tangrowth["evoMove"], "Ancient Power");
+    let sylveon = &pokedex["sylveon"];
+    assert_eq!(sylveon["evoType"], "levelExtra");
+    assert_eq!(
+        sylveon["evoCondition"],
+        "with a Fairy-type move and two levels of Affection"
+    );
+    assert_eq!(sylveon["evoMove"], json!(null));
+}
+
+#[test]
+fn reads_held_item_evolutions() {
+    let mut romfs = Romfs::default();
+    let oval_stone = romfs.add_item("Oval Stone");
+    let razor_fang = romfs.add_item("Razor Fang");
+    let species = Personal {
+        stats: [65; 6],
+        ..Default::default()
+    };
+    romfs.add_species(113, "Chansey", species.clone());
+    romfs.add_species(207, "Gligar", species.clone()).evolutions = vec![(20, razor_fang, 472, 0)];
+    romfs
+        .add_species(440, "Happiny", species.clone())
+        .evolutions = vec![(19, oval_stone, 113, 0)];
+    romfs.add_species(472, "Gliscor", species);
+    let pokedex = romfs.write().pokedex(Options::default());
+
+    let chansey = &pokedex["chansey"];
+    assert_eq!(chansey["evoType"], "levelHold");
+    assert_eq!(chansey["evoItem"], "Oval Stone");
+    assert_eq!(chansey["evoCondition"], "during the day");
+    let gliscor = &pokedex["gliscor"];
+    assert_eq!(gliscor["evoType"], "levelHold");
+    assert_eq!(gliscor["evoItem"], "Razor Fang");
+    assert_eq!(gliscor["evoCondition"], "during the night");
+}
+
+/// Bit 7 of `sprite_bits` marks a separate female model, the low bits are the dex color
+#[test]
+fn reads_gender_differences() {
+    let mut romfs = Romfs::default();
+    let species = |sprite_bits| Personal {
+        stats: [35, 55, 40, 90, 50, 50],
+        sprite_bits,
+        ..Default::default()
+    };
+    // Both yellow
+    romfs.add_species(25, "Pikachu", species(0x80 | 2));
+    romfs.add_species(26, "Raichu", species(2));
+    let pokedex = romfs.write().pokedex(Options::default());
+
+    assert_eq!(pokedex["pikachu"]["genderDifferences"], true);
+    assert_eq!(pokedex["raichu"]["genderDifferences"], json!(null));
+}
+
+/// Base Meowstic is the male, the female is its `F` forme with her own abilities
+#[test]
+fn splits_meowstic_by_gender() {
+    const MEOWSTIC: usize = 678;
+    let mut romfs = Romfs::default();
+    let keen_eye = romfs.add_ability("Keen Eye");
+    let prankster = romfs.add_ability("Prankster");
+    let competitive = romfs.add_ability("Competitive");
+    let meowstic = |hidden| Personal {
+        stats: [74, 48, 76, 104, 83, 81],
+        types: (13, 13),
+        gender: 127,
+        abilities: [keen_eye, keen_eye, hidden],
+        ..Default::default()
+    };
+    romfs.add_species(MEOWSTIC, "Meowstic", meowstic(prankster));
+    romfs.add_formes(MEOWSTIC, vec![meowstic(competitive)]);
+    let pokedex = romfs.write().pokedex(Options::default());
+
+    let male = &pokedex["meowstic"];
+    assert_eq!(male["gender"], "M");
+    assert_eq!(male["genderRatio"], json!(null));
+    assert_eq!(male["formes"], json!(["Meowstic", "Meowstic-F"]));
+    assert_eq!(male["abilities"]["H"], "Prankster");
+    let female = &pokedex["meowsticf"];
+    assert_eq!(female["name"], "Meowstic-F");
+    assert_eq!(female["gender"], "F");
+    assert_eq!(female["genderRatio"], json!(null));
+    assert_eq!(female["baseSpecies"], "Meowstic");
+    assert_eq!(female["forme"], "F");
+    assert_eq!(female["abilities"]["H"], "Competitive");
+    assert_eq!(female["baseStats"]["spa"], 83);
+}
+
+/// Only formes stored after the species are read, a stat-less or in-range `form_stats_id`
+/// keeps the base species alone
+#[test]
+fn skips_formes_without_their_own_stats() {
+    let mut romfs = Romfs::default();
+    let species = |form_count, form_stats_id| Personal {
+        stats: [50; 6],
+        form_count,
+        form_stats_id,
+        ..Default::default()
+    };
+    romfs.add_species(201, "Unown", species(28, 0));
+    // Corrupt, points at Unown
+    romfs.add_species(479, "Rotom", species(6, 201));
+    romfs.add_species(386, "Deoxys", species(1, 0));
+    romfs.add_formes(386, vec![species(1, 0); 3]);
+    let pokedex = romfs.write().pokedex(Options::default());
+
+    let ids: Vec<&String> = pokedex.as_object().unwrap().keys().collect();
+    assert_eq!(
+        ids,
+        [
+            "unown",
+            "deoxys",
+            "deoxysattack",
+            "deoxysdefense",
+            "deoxysspeed",
+            "rotom"
+        ]
+    );
+}
+
+#[test]
+fn makes_ultra_necrozma_battle_only() {
+    const NECROZMA: usize = 800;
+    let mut romfs = Romfs::default();
+    let necrozma = Personal {
+        stats: [97, 107, 101, 79, 127, 89],
+        types: (13, 13),
+        ..Default::default()
+    };
+    romfs.add_species(NECROZMA, "Necrozma", necrozma.clone());
+    romfs.add_formes(NECROZMA, vec![necrozma; 3]);
+    let pokedex = romfs.write().pokedex(Options::default());
+
+    for id in ["necrozmaduskmane", "necrozmadawnwings"] {
+        assert_eq!(pokedex[id]["battleOnly"], json!(null), "{id}");
+        assert_eq!(pokedex[id]["baseSpecies"], "Necrozma", "{id}");
+    }
+    let ultra = &pokedex["necrozmaultra"];
+    assert_eq!(
+        ultra["battleOnly"],
+        json!(["Necrozma-Dawn-Wings", "Necrozma-Dusk-Mane"])
+    );
+    assert_eq!(ultra["requiredItems"], json!(["Ultranecrozium Z"]));
+}
+
+/// The ROM's curly apostrophe is straightened in the name, the id drops it either way
+#[test]
+fn straightens_farfetchd_apostrophe() {
+    let mut romfs = Romfs::default();
+    let farfetchd = Personal {
+        stats: [52, 90, 55, 60, 58, 62],
+        types: (0, 2),
+        ..Default::default()
+    };
+    romfs.add_species(83, "Farfetch’d", farfetchd);
+    let pokedex = romfs.write().pokedex(Options::default());
+
+    assert_eq!(pokedex["farfetchd"]["name"], "Farfetch'd");
+}
+
+/// Each language's text GARC uses the English text ids
+#[test]
+fn names_species_in_two_languages() {
+    let romfs = |name| {
+        let mut romfs = Romfs::default();
+        let bulbasaur = Personal {
+            stats: [45, 49, 49, 45, 65, 65],
+            ..Default::default()
+        };
+        romfs.add_species(1, name, bulbasaur);
+        romfs
+    };
+    let rom = romfs("Bulbasaur").write();
+    let french_garc = Language::French.text_garc();
+    write_garc(&rom.0, french_garc, &romfs("Bulbizarre").text_garc());
+    let rom_data = RomData::new(&rom.0);
+
+    let pokedex = rom.pokedex(Options::default());
+    assert_eq!(pokedex["bulbasaur"]["name"], "Bulbasaur");
+
+    let options = Options {
+        language: Some(Language::French),
+        ..rom.options(Options::default())
+    };
+    let text_path = rom.0.join(garc_files::BASE_PATH).join(french_garc);
+    let text_files = garc::read_files::<TextFile>(&garc::open(&text_path).unwrap()).unwrap();
+    let (pokedex, _) = pokemon::build_pokedex(&rom_data, &text_files, &options).unwrap();
+    assert_eq!(pokedex["bulbizarre"].name, "Bulbizarre");
+    assert_eq!(pokedex["bulbizarre"].num, 1);
+}
+
+#[test]
+fn marks_totem_formes() {
+    const MAROWAK: usize = 105;
+    let mut romfs = Romfs::default();
+    let marowak = Personal {
+        stats: [60, 80, 110, 45, 50, 80],
+        types: (4, 4),
+        ..Default::default()
+    };
+    romfs.add_species(MAROWAK, "Marowak", marowak.clone());
+    let alola = Personal {
+        types: (9, 7),
+        ..marowak
+    };
+    romfs.add_formes(MAROWAK, vec![alola.clone(), alola]);
+    let pokedex = romfs.write().pokedex(Options::default());
+
+    for id in ["marowak", "marowakalola"] {
+        assert_eq!(pokedex[id]["isNonstandard"], json!(null), "{id}");
+    }
+    let totem = &pokedex["marowakalolatotem"];
+    assert_eq!(totem["isNonstandard"], "Unobtainable");
+    assert_eq!(totem["types"], json!(["Fire", "Ghost"]));
+}
+
+/// With `--compact-formes`, formes identical to their base become cosmetic
+#[test]
+fn compacts_identical_formes() {
+    const DEOXYS: usize = 386;
+    const VIVILLON: usize = 666;
+    let mut romfs = Romfs::default();
+    let deoxys = Personal {
+        stats: [50, 150, 50, 150, 150, 50],
+        types: (13, 13),
+        ..Default::default()
+    };
+    romfs.add_species(DEOXYS, "Deoxys", deoxys.clone());
+    let vivillon = Personal {
+        stats: [80, 52, 50, 89, 90, 50],
+        types: (6, 2),
+        ..Default::default()
+    };
+    romfs.add_species(VIVILLON, "Vivillon", vivillon.clone());
+    let deoxys_formes = [
+        [50, 180, 20, 150, 180, 20],
+        [50, 70, 160, 90, 70, 160],
+        [50, 95, 90, 180, 95, 90],
+    ];
+    romfs.add_formes(
+        DEOXYS,
+        deoxys_formes
+            .into_iter()
+            .map(|stats| Personal {
+                stats,
+                ..deoxys.clone()
+            })
+            .collect(),
+    );
+    romfs.add_formes(VIVILLON, vec![vivillon; 2]);
+    let rom = romfs.write();
+    let compact = Options {
+        compact_formes: true,
+        ..Default::default()
+    };
+
+    let pokedex = rom.pokedex(Options::default());
+    assert_eq!(pokedex["vivillon"]["formes"].as_array().unwrap().len(), 3);
+    let pokedex = rom.pokedex(compact);
+    assert_eq!(pokedex["vivillonfancy"], json!(null));
+    assert_eq!(pokedex["vivillonpokeball"], json!(null));
+    assert_eq!(pokedex["vivillon"]["formes"], json!(null));
+    assert_eq!(
+        pokedex["vivillon"]["cosmeticFormes"],
+        json!(["Vivillon-Fancy", "Vivillon-Pokeball"])
+    );
+    assert_eq!(
+        pokedex["deoxys"]["formes"],
+        json!(["Deoxys", "Deoxys-Attack", "Deoxys-Defense", "Deoxys-Speed"])
+    );
+    assert_eq!(pokedex["deoxysspeed"]["baseStats"]["spe"], 180);
+}
+
+#[test]
+fn names_the_base_forme() {
+    const DEOXYS: usize = 386;
+    let mut romfs = Romfs::default();
+    let species = Personal {
+        stats: [50, 150, 50, 150, 150, 50],
+        types: (13, 13),
+        ..Default::default()
+    };
+    romfs.add_species(DEOXYS, "Deoxys", species.clone());
+    romfs.add_formes(DEOXYS, vec![species.clone(); 3]);
+    romfs.add_species(DEOXYS + 1, "Turtwig", species);
+    let pokedex = romfs.write().pokedex(Options::default());
+
+    assert_eq!(pokedex["deoxys"]["baseForme"], "Normal");
+    assert_eq!(pokedex["deoxysattack"]["baseForme"], json!(null));
+    assert_eq!(pokedex["turtwig"]["baseForme"], json!(null));
+}
+
+/// Average is the base species, the other sizes are formes with their own stats
+#[test]
+fn reads_pumpkin_sizes() {
+    let mut romfs = Romfs::default();
+    let species = |stats| Personal {
+        stats,
+        types: (7, 11),
+        ..Default::default()
+    };
+    // Average, Small, Large and Super, in ROM stat order
+    let pumpkaboo = [
+        [49, 66, 70, 51, 44, 55],
+        [44, 66, 70, 56, 44, 55],
+        [54, 66, 70, 46, 44, 55],
+        [59, 66, 70, 41, 44, 55],
+    ];
+    let gourgeist = [
+        [65, 90, 122, 84, 58, 75],
+        [55, 85, 122, 99, 58, 75],
+        [75, 95, 122, 69, 58, 75],
+        [85, 100, 122, 54, 58, 75],
+    ];
+    for (index, name, sizes) in [(710, "Pumpkaboo", pumpkaboo), (711, "Gourgeist", gourgeist)] {
+        romfs.add_species(index, name, species(sizes[0]));
+    }
+    for (index, sizes) in [(710, pumpkaboo), (711, gourgeist)] {
+        romfs.add_formes(
+            index,
+            sizes[1..].iter().map(|stats| species(*stats)).collect(),
+        );
+    }
+    let pokedex = romfs.write().pokedex(Options::default());
+
+    for (name, sizes) in [("Pumpkaboo", pumpkaboo), ("Gourgeist", gourgeist)] {
+        let id = name.to_lowercase();
+        assert_eq!(pokedex[&id]["baseForme"], "Average");
+        let formes = ["", "-Small", "-Large", "-Super"].map(|size| format!("{name}{size}"));
+        assert_eq!(pokedex[&id]["formes"], json!(formes));
+        for (forme, stats) in formes.iter().zip(sizes) {
+            let entry = &pokedex[forme.replace('-', "").to_lowercase()];
+            assert_eq!(entry["baseStats"]["hp"], stats[0], "{forme}");
+            assert_eq!(entry["baseStats"]["spe"], stats[3], "{forme}");
+        }
+    }
+}
+
+/// Formes without a weight of their own share their base species'
+#[test]
+fn gives_every_species_a_weight() {
+    const DEOXYS: usize = 386;
+    let mut romfs = Romfs::default();
+    let deoxys = Personal {
+        stats: [50, 150, 50, 150, 150, 50],
+        types: (13, 13),
+        weight: 600,
+        ..Default::default()
+    };
+    romfs.add_species(DEOXYS, "Deoxys", deoxys.clone());
+    let weightless = Personal {
+        weight: 0,
+        ..deoxys
+    };
+    romfs.add_formes(DEOXYS, vec![weightless; 3]);
+    let pokedex = romfs.write().pokedex(Options::default());
+
+    for (id, entry) in pokedex.as_object().unwrap() {
+        assert_eq!(entry["weightkg"], 60.0, "{id}");
+    }
+}
+
+/// An evolution method without a mapping shows up as "other" with its raw numbers
+#[test]
+fn keeps_unmapped_evolution_methods_visible() {
+    let mut romfs = Romfs::default();
+    let species = Personal {
+        stats: [65; 6],
+        ..Default::default()
+    };
+    romfs
+        .add_species(1, "Bulbasaur", species.clone())
+        .evolutions = vec![(40, 7, 2, 0)];
+    romfs.add_species(2, "Ivysaur", species);
+    let pokedex = romfs.write().pokedex(Options::default());
+
+    let ivysaur = &pokedex["ivysaur"];
+    assert_eq!(ivysaur["prevo"], "Bulbasaur");
+    assert_eq!(ivysaur["evoType"], "other");
+    assert_eq!(
+        ivysaur["evoCondition"],
+        "evolution method 40 with argument 7"
+    );
+}
+
+/// The Alola starters' hidden abilities are unreleased in both rom types, other species
+/// with a hidden ability aren't flagged
+#[test]
+fn flags_unreleased_hidden_abilities_per_rom_type() {
+    let mut romfs = Romfs::default();
+    let overgrow = romfs.add_ability("Overgrow");
+    let long_reach = romfs.add_ability("Long Reach");
+    let species = Personal {
+        stats: [68, 55, 55, 50, 50, 42],
+        types: (11, 2),
+        abilities: [overgrow, overgrow, long_reach],
+        ..Default::default()
+    };
+    romfs.add_species(722, "Rowlet", species.clone());
+    romfs.add_species(731, "Pikipek", species);
+    let rom = romfs.write();
+    for rom_type in [RomType::SunMoon, RomType::UltraSunMoon] {
+        let pokedex = rom.pokedex(Options {
+            rom_type,
+            ..Default::default()
+        });
+        assert_eq!(pokedex["rowlet"]["unreleasedHidden"], true, "{rom_type:?}");
+        assert_eq!(
+            pokedex["pikipek"]["unreleasedHidden"],
+            json!(null),
+            "{rom_type:?}"
+        );
+    }
+}
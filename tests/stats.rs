@@ -0,0 +1,57 @@
+//! Checks the stat formulas against Garchomp's known stats
+
+use std::io::Cursor;
+
+use binrw::BinRead;
+use usum_extractor::{calc_hp, calc_stat, Stats};
+
+/// HP, Atk, Def, SpA, SpD, Spe
+const GARCHOMP: [u8; 6] = [108, 130, 95, 80, 85, 102];
+
+#[test]
+fn computes_level_100_stats() {
+    let [hp, stats @ ..] = GARCHOMP;
+    assert_eq!(calc_hp(hp, 31, 0, 100), 357);
+    assert_eq!(calc_hp(hp, 31, 252, 100), 420);
+    let neutral = stats.map(|base| calc_stat(base, 31, 0, 100, 1.0));
+    assert_eq!(neutral, [296, 226, 196, 206, 240]);
+    // Jolly, 252 Atk and Spe
+    assert_eq!(calc_stat(GARCHOMP[1], 31, 252, 100, 1.0), 359);
+    assert_eq!(calc_stat(GARCHOMP[5], 31, 252, 100, 1.1), 333);
+    assert_eq!(calc_stat(GARCHOMP[3], 31, 0, 100, 0.9), 176);
+}
+
+#[test]
+fn computes_level_50_stats() {
+    assert_eq!(calc_hp(GARCHOMP[0], 31, 0, 50), 183);
+    assert_eq!(calc_stat(GARCHOMP[1], 31, 0, 50, 1.0), 150);
+    assert_eq!(calc_stat(GARCHOMP[5], 31, 252, 50, 1.1), 169);
+    assert_eq!(calc_stat(GARCHOMP[3], 0, 0, 50, 0.9), 76);
+}
+
+#[test]
+fn keeps_shedinja_at_1_hp() {
+    assert_eq!(calc_hp(1, 31, 252, 100), 1);
+}
+
+/// `iter` and indexing follow the ROM layout, Spe before SpA and SpD
+#[test]
+fn iterates_stats_in_rom_order() {
+    let stats = Stats::read_le(&mut Cursor::new([1, 2, 3, 4, 5, 6])).unwrap();
+    assert_eq!(stats.spe, 4);
+    assert_eq!(
+        stats.iter().collect::<Vec<_>>(),
+        [
+            ("hp", 1),
+            ("atk", 2),
+            ("def", 3),
+            ("spe", 4),
+            ("spa", 5),
+            ("spd", 6)
+        ]
+    );
+    assert_eq!(
+        (0..6).map(|i| stats[i]).collect::<Vec<_>>(),
+        [1, 2, 3, 4, 5, 6]
+    );
+}
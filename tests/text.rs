@@ -0,0 +1,53 @@
+//! Text files decoded with other keys than the games'
+
+mod common;
+
+use std::io::Cursor;
+
+use binrw::BinRead;
+use common::text_file_with_keys;
+use usum_extractor::text::{find_keys, TextDecoder, TextFile};
+
+#[test]
+fn decodes_with_custom_keys() {
+    let bytes = text_file_with_keys(&["Bulbasaur", "Ivysaur", "Venusaur"], 0x1234, 0x0f0f);
+    let decoder = TextDecoder {
+        key_base: 0x1234,
+        key_advance: 0x0f0f,
+        ..TextDecoder::GEN7
+    };
+    let file = TextFile::read_le_args(&mut Cursor::new(&bytes), (decoder,)).unwrap();
+    assert_eq!(file.lines, ["Bulbasaur", "Ivysaur", "Venusaur"]);
+
+    let file = TextFile::read_le_args(&mut Cursor::new(&bytes), (TextDecoder::GEN7,)).unwrap();
+    assert_ne!(file.lines[0], "Bulbasaur");
+}
+
+#[test]
+fn applies_substitutions() {
+    let bytes = text_file_with_keys(&["Farfetch’d", "Flabébé"], 0x7c89, 0x2983);
+    let file = TextFile::read_le(&mut Cursor::new(&bytes)).unwrap();
+    assert_eq!(file.lines, ["Farfetch'd", "Flabebe"]);
+
+    let decoder = TextDecoder {
+        substitutions: &[],
+        ..TextDecoder::GEN7
+    };
+    let file = TextFile::read_le_args(&mut Cursor::new(&bytes), (decoder,)).unwrap();
+    assert_eq!(file.lines, ["Farfetch’d", "Flabébé"]);
+}
+
+#[test]
+fn finds_keys_from_a_known_line() {
+    let bytes = text_file_with_keys(&["Egg", "Bulbasaur", "Ivysaur", "Venusaur"], 0x1234, 0x0f0f);
+    let found = find_keys(&bytes, 1, "Bulbasaur").unwrap();
+    assert_eq!(
+        found,
+        [TextDecoder {
+            key_base: 0x1234,
+            key_advance: 0x0f0f,
+            ..TextDecoder::GEN7
+        }]
+    );
+    assert!(find_keys(&bytes, 2, "Bulbasaur").unwrap().is_empty());
+}
@@ -0,0 +1,64 @@
+//! Checks which outputs a rerun skips
+
+mod common;
+
+use common::{Romfs, TempRom};
+use serde_json::json;
+use usum_extractor::{
+    is_up_to_date,
+    options::{LearnsetFormat, Options},
+    write_output,
+};
+
+#[test]
+fn rebuilds_outputs_written_with_other_options() {
+    let rom = Romfs::default().write();
+    let out = TempRom::new();
+    std::fs::create_dir_all(&out.0).unwrap();
+    let options = rom.options(Options::default());
+    let up_to_date = |options: &Options| is_up_to_date(&rom.0, &out.0, "learnsets", options);
+
+    assert!(!up_to_date(&options));
+    write_output(&out.0, "learnsets", &json!({}), &options).unwrap();
+    assert!(up_to_date(&options));
+
+    let showdown = Options {
+        learnset_format: LearnsetFormat::Showdown,
+        ..options.clone()
+    };
+    assert!(!up_to_date(&showdown));
+    let flattened = Options {
+        flatten_formes: true,
+        ..options.clone()
+    };
+    assert!(!up_to_date(&flattened));
+    let tabs = Options {
+        indent: Some("\t".to_owned()),
+        ..options.clone()
+    };
+    assert!(!up_to_date(&tabs));
+
+    write_output(&out.0, "learnsets", &json!({}), &tabs).unwrap();
+    assert!(up_to_date(&tabs));
+    assert!(!up_to_date(&options));
+}
+
+#[test]
+fn only_rebuild_regenerates_up_to_date_outputs() {
+    let rom = Romfs::default().write();
+    let out = TempRom::new();
+    std::fs::create_dir_all(&out.0).unwrap();
+    let options = rom.options(Options::default());
+    write_output(&out.0, "learnsets", &json!({}), &options).unwrap();
+
+    let args = |flag: &str| {
+        let args = ["rom", "out", flag].map(str::to_owned);
+        rom.options(Options::from_args(args.into_iter()).unwrap())
+    };
+    let forced = args("--force");
+    assert!(forced.force && !forced.rebuild);
+    assert!(is_up_to_date(&rom.0, &out.0, "learnsets", &forced));
+    let rebuilt = args("--rebuild");
+    assert!(rebuilt.rebuild && !rebuilt.force);
+    assert!(!is_up_to_date(&rom.0, &out.0, "learnsets", &rebuilt));
+}
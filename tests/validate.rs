@@ -0,0 +1,73 @@
+//! Runs the ROM checks of `--validate-only` on a synthetic romfs
+
+mod common;
+
+use common::{Move, Romfs};
+use usum_extractor::{options::Options, validate};
+
+#[test]
+fn lists_suspicious_moves() {
+    let mut romfs = Romfs::default();
+    let tackle = Move {
+        category: 1,
+        power: 40,
+        accuracy: 100,
+        pp: 35,
+        ..Default::default()
+    };
+    romfs.add_move("Tackle", tackle.clone());
+    romfs.add_move(
+        "Broken Tackle",
+        Move {
+            power: 0,
+            ..tackle.clone()
+        },
+    );
+    // Variable power, the damage is computed in battle
+    romfs.add_move(
+        "Low Kick",
+        Move {
+            power: 0,
+            ..tackle.clone()
+        },
+    );
+    romfs.add_move(
+        "Growl",
+        Move {
+            power: 30,
+            category: 0,
+            ..tackle.clone()
+        },
+    );
+    romfs.add_move(
+        "Missed",
+        Move {
+            accuracy: 0,
+            ..tackle.clone()
+        },
+    );
+    romfs.add_move(
+        "Far Off",
+        Move {
+            target: 14,
+            ..tackle
+        },
+    );
+    let rom = romfs.write();
+
+    let options = rom.options(Options::default());
+    let suspicious = validate::check_moves(&rom.0, &options).unwrap();
+    let kinds: Vec<(&str, Vec<&str>)> = suspicious
+        .iter()
+        .map(|(kind, ids)| (*kind, ids.iter().map(String::as_str).collect()))
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            ("damaging with no base power", vec!["brokentackle"]),
+            ("status with a base power", vec!["growl"]),
+            ("accuracy 0", vec!["missed"]),
+            ("unknown target", vec!["faroff"]),
+        ]
+    );
+}